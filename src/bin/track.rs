@@ -1,14 +1,14 @@
+use std::process::ExitCode;
+
 use error_stack::{Result, ResultExt};
 
 use time_tracker::{error::AppError, feature::cli, init};
 
-fn main() -> Result<(), AppError> {
+fn main() -> Result<ExitCode, AppError> {
     init::error_reporting();
     init::tracing();
 
     cli::run()
         .change_context(AppError)
-        .attach_printable("failed to run CLI")?;
-
-    Ok(())
+        .attach_printable("failed to run CLI")
 }
@@ -1 +1,3 @@
 //! Functionality shared between features
+
+pub mod time;
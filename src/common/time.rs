@@ -0,0 +1,346 @@
+//! Points in time written by the user
+//!
+//! Times are read and shown in the user's timezone: the one configured, the
+//! one named by `TZ` if `chrono-tz` knows it, or the system's otherwise.
+
+use std::time::Duration;
+
+use chrono::{
+    DateTime, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta,
+    TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use error_stack::{Result, ResultExt};
+
+use crate::error::Suggestion;
+
+const TIME_FORMATS: &[&str] = &["%H:%M", "%H:%M:%S"];
+const DATE_TIME_FORMATS: &[&str] = &["%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M:%S"];
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid time")]
+pub struct TimeParseError;
+
+/// Timezone the user reads and writes times in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Zone {
+    /// The system's timezone
+    #[default]
+    Local,
+    /// A timezone from the IANA database, e.g. `Europe/Berlin`
+    Named(Tz),
+}
+
+impl Zone {
+    /// The zone named by the `TZ` environment variable, or the system's
+    pub fn from_env() -> Self {
+        std::env::var("TZ")
+            .ok()
+            .and_then(|name| name.parse().ok())
+            .map_or(Self::Local, Self::Named)
+    }
+
+    /// Reads an IANA timezone name such as `Europe/Berlin`, or `local` for
+    /// the system's timezone
+    pub fn parse(name: &str) -> Result<Self, TimeParseError> {
+        if name.eq_ignore_ascii_case("local") {
+            return Ok(Self::Local);
+        }
+        name.parse()
+            .map(Self::Named)
+            .map_err(|_| error_stack::Report::new(TimeParseError))
+            .attach_printable_lazy(|| format!("unknown timezone '{name}'"))
+            .attach(Suggestion(
+                "use a name from the IANA database like `Europe/Berlin`, or `local`",
+            ))
+    }
+
+    /// `time` in this zone, with the offset from UTC in effect at that time
+    pub fn with_offset(self, time: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Self::Local => time.with_timezone(&Local).fixed_offset(),
+            Self::Named(tz) => time.with_timezone(&tz).fixed_offset(),
+        }
+    }
+
+    /// The wall-clock time of `time` in this zone
+    pub fn local_time(self, time: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Self::Local => time.with_timezone(&Local).naive_local(),
+            Self::Named(tz) => time.with_timezone(&tz).naive_local(),
+        }
+    }
+
+    /// The calendar date of `time` in this zone
+    pub fn date_of(self, time: DateTime<Utc>) -> NaiveDate {
+        self.local_time(time).date()
+    }
+
+    /// The first instant of `date` in this zone
+    ///
+    /// Where the clocks skip midnight, the day starts once they resume.
+    pub fn start_of_day(self, date: NaiveDate) -> DateTime<Utc> {
+        let midnight = date.and_time(NaiveTime::MIN);
+        (0..24 * 4)
+            .map(|quarter| midnight + TimeDelta::minutes(15 * quarter))
+            .find_map(|local| self.to_utc(local))
+            .unwrap_or_else(|| midnight.and_utc())
+    }
+
+    /// The instant a wall-clock time in this zone refers to
+    ///
+    /// Times repeated when the clocks go back resolve to their first
+    /// occurrence, times skipped when they go forward don't exist.
+    pub fn to_utc(self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        fn earliest<T: TimeZone>(result: LocalResult<DateTime<T>>) -> Option<DateTime<Utc>> {
+            result.earliest().map(|time| time.with_timezone(&Utc))
+        }
+
+        match self {
+            Self::Local => earliest(Local.from_local_datetime(&local)),
+            Self::Named(tz) => earliest(tz.from_local_datetime(&local)),
+        }
+    }
+}
+
+impl std::fmt::Display for Zone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Local => f.write_str("local"),
+            Self::Named(tz) => f.write_str(tz.name()),
+        }
+    }
+}
+
+/// Parses an absolute or relative time, e.g. `09:15`, `2026-10-17 14:00`
+/// or `20m ago`
+///
+/// A time of day without a date refers to its last occurrence before `now`,
+/// a date without a time to its midnight.
+pub fn parse_time(
+    input: &str,
+    now: DateTime<Utc>,
+    zone: Zone,
+) -> Result<DateTime<Utc>, TimeParseError> {
+    let input = input.trim();
+
+    if input.ends_with("ago") {
+        return parse_ago(input, now);
+    }
+
+    let local = if let Some(local) = parse_with(input, DATE_TIME_FORMATS, |s, f| {
+        NaiveDateTime::parse_from_str(s, f)
+    }) {
+        local
+    } else if let Ok(date) = NaiveDate::parse_from_str(input, DATE_FORMAT) {
+        date.and_time(NaiveTime::MIN)
+    } else if let Some(time) = parse_with(input, TIME_FORMATS, NaiveTime::parse_from_str) {
+        let today = zone.date_of(now).and_time(time);
+        match zone.to_utc(today) {
+            Some(utc) if utc > now => today - TimeDelta::days(1),
+            _ => today,
+        }
+    } else {
+        return Err(TimeParseError)
+            .attach_printable(format!("unrecognised time '{input}'"))
+            .attach(Suggestion(
+                "use a time like `09:15`, `2026-10-17`, `2026-10-17 14:00` or `20m ago`",
+            ));
+    };
+
+    zone.to_utc(local)
+        .ok_or(TimeParseError)
+        .attach_printable_lazy(|| format!("'{input}' does not exist in the {zone} timezone"))
+        .attach(Suggestion("pick a time outside the daylight saving change"))
+}
+
+/// Parses a length of time such as `15m` or `1h30m`
+pub fn parse_duration(input: &str) -> Result<Duration, TimeParseError> {
+    if input.trim_end().ends_with("ago") {
+        return Err(TimeParseError)
+            .attach_printable(format!(
+                "'{input}' is a point in time, not a length of time"
+            ))
+            .attach(Suggestion("leave out the `ago`"));
+    }
+    parse_offset(input)
+}
+
+/// Parses a length of time such as `20m` and counts it back from `now`
+pub fn parse_ago(input: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, TimeParseError> {
+    let offset = parse_offset(input)?;
+    TimeDelta::from_std(offset)
+        .ok()
+        .and_then(|offset| now.checked_sub_signed(offset))
+        .ok_or(TimeParseError)
+        .attach_printable_lazy(|| format!("'{input}' is too long ago"))
+}
+
+/// Parses a length of time such as `20m`, `1h30m` or `90s`, optionally
+/// followed by `ago`
+fn parse_offset(input: &str) -> Result<Duration, TimeParseError> {
+    let invalid = || {
+        error_stack::Report::new(TimeParseError)
+            .attach_printable(format!("unrecognised length of time '{input}'"))
+            .attach(Suggestion("use a length of time like `20m` or `1h30m`"))
+    };
+
+    let trimmed = input.trim();
+    let trimmed = trimmed.strip_suffix("ago").unwrap_or(trimmed).trim_end();
+    if trimmed.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = trimmed;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let amount = rest[..digits].parse::<u64>().map_err(|_| invalid())?;
+        let unit = match rest[digits..].chars().next() {
+            Some('h') => 60 * 60,
+            Some('m') => 60,
+            Some('s') => 1,
+            _ => return Err(invalid()),
+        };
+        total += Duration::from_secs(amount.checked_mul(unit).ok_or_else(invalid)?);
+        rest = rest[digits + 1..].trim_start();
+    }
+
+    Ok(total)
+}
+
+fn parse_with<T, P>(input: &str, formats: &[&str], parse: P) -> Option<T>
+where
+    P: Fn(&str, &str) -> std::result::Result<T, chrono::ParseError>,
+{
+    formats.iter().find_map(|format| parse(input, format).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Europe::Berlin;
+
+    use super::*;
+
+    const BERLIN: Zone = Zone::Named(Berlin);
+
+    fn berlin(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Berlin
+            .with_ymd_and_hms(y, mo, d, h, mi, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn time_of_day_refers_to_today() {
+        let now = berlin(2026, 10, 18, 12, 0);
+
+        let time = parse_time("09:15", now, BERLIN).unwrap();
+
+        assert_eq!(time, berlin(2026, 10, 18, 9, 15));
+    }
+
+    #[test]
+    fn time_of_day_later_than_now_refers_to_yesterday() {
+        let now = berlin(2026, 10, 18, 0, 30);
+
+        let time = parse_time("23:50", now, BERLIN).unwrap();
+
+        assert_eq!(time, berlin(2026, 10, 17, 23, 50));
+    }
+
+    #[test]
+    fn date_and_time_are_read_in_the_zone() {
+        let now = berlin(2026, 10, 18, 12, 0);
+
+        let time = parse_time("2026-10-17 14:00", now, BERLIN).unwrap();
+
+        assert_eq!(time, berlin(2026, 10, 17, 14, 0));
+    }
+
+    #[test]
+    fn date_refers_to_midnight() {
+        let now = berlin(2026, 10, 18, 12, 0);
+
+        let time = parse_time("2026-10-01", now, BERLIN).unwrap();
+
+        assert_eq!(time, berlin(2026, 10, 1, 0, 0));
+    }
+
+    #[test]
+    fn relative_time_counts_back_from_now() {
+        let now = berlin(2026, 10, 18, 12, 0);
+
+        let time = parse_time("1h20m ago", now, BERLIN).unwrap();
+
+        assert_eq!(time, berlin(2026, 10, 18, 10, 40));
+    }
+
+    #[test]
+    fn skipped_time_is_rejected() {
+        // clocks jump from 02:00 to 03:00 on the last Sunday in March
+        let now = berlin(2026, 3, 29, 12, 0);
+
+        let time = parse_time("2026-03-29 02:30", now, BERLIN);
+
+        assert!(time.is_err());
+    }
+
+    #[test]
+    fn offsets_need_a_unit() {
+        assert_eq!(parse_offset("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_offset("1h 30m").unwrap(), Duration::from_secs(5400));
+        assert!(parse_offset("20").is_err());
+        assert!(parse_offset("ago").is_err());
+    }
+
+    #[test]
+    fn offset_follows_daylight_saving_time() {
+        // clocks go back from 03:00 to 02:00 on 2026-10-25
+        let before = BERLIN.with_offset(berlin(2026, 10, 25, 1, 30));
+        let after = BERLIN.with_offset(berlin(2026, 10, 25, 12, 0));
+
+        assert_eq!(before.to_rfc3339(), "2026-10-25T01:30:00+02:00");
+        assert_eq!(after.to_rfc3339(), "2026-10-25T12:00:00+01:00");
+    }
+
+    #[test]
+    fn repeated_time_is_the_first_occurrence() {
+        // 02:30 happens twice as clocks go back from 03:00 to 02:00
+        let now = berlin(2026, 10, 25, 12, 0);
+
+        let time = parse_time("2026-10-25 02:30", now, BERLIN).unwrap();
+
+        assert_eq!(
+            BERLIN.with_offset(time).to_rfc3339(),
+            "2026-10-25T02:30:00+02:00"
+        );
+    }
+
+    #[test]
+    fn zones_are_named_or_local() {
+        assert_eq!(Zone::parse("local").unwrap(), Zone::Local);
+        assert_eq!(Zone::parse("Europe/Berlin").unwrap(), BERLIN);
+        assert!(Zone::parse("Mars/Olympus_Mons").is_err());
+    }
+
+    #[test]
+    fn durations_are_not_ago() {
+        assert_eq!(parse_duration("15m").unwrap(), Duration::from_secs(900));
+        assert!(parse_duration("15m ago").is_err());
+    }
+
+    #[test]
+    fn day_starts_when_clocks_resume_after_skipped_midnight() {
+        // clocks in Santiago jump from 00:00 to 01:00 on 2026-09-06
+        let zone = Zone::Named(chrono_tz::America::Santiago);
+        let date = NaiveDate::from_ymd_opt(2026, 9, 6).unwrap();
+
+        let start = zone.start_of_day(date);
+
+        assert_eq!(zone.local_time(start), date.and_hms_opt(1, 0, 0).unwrap());
+    }
+}
@@ -1,3 +1,7 @@
 pub mod cli;
+pub mod config;
+pub mod editor;
+pub mod export;
 pub mod report_fmt;
+pub mod rounding;
 pub mod tracker;
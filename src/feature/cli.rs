@@ -1,28 +1,505 @@
-use std::{path::PathBuf, time::Duration};
+use std::{collections::BTreeMap, path::PathBuf, process::ExitCode, time::Duration};
 
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, TimeDelta, Utc, Weekday};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use error_stack::{Result, ResultExt};
 
 use crate::{
+    common::time::{parse_ago, parse_duration, parse_time, Zone},
     error::Suggestion,
     feature::{
-        report_fmt::{DurationFormatter, HourMinSecFormatter},
-        tracker::{FlatFileTracker, ReportTimespan, Reporter, StartupStatus, Tracker},
+        config, editor,
+        export::{self, Format},
+        report_fmt::{
+            CompactFormatter, DecimalHoursFormatter, DurationFormatter, HourMinSecFormatter,
+            HumanFormatter, IsoFormatter,
+        },
+        rounding::{self, Rounding},
+        tracker::{
+            Breakdown, EndTime, FlatFileTracker, GroupBy, GroupKey, JournalTracker, LockfileData,
+            Overlap, PauseStatus, RecordChanges, RecordEdit, RecordId, ReportQuery, ReportTimespan,
+            Reporter, ResumeStatus, SessionDetails, SqliteTracker, StartTime, StartupStatus,
+            TimeRecord, Tracker, TrackerState, DEFAULT_LOCK_TIMEOUT,
+        },
     },
 };
 
+/// Exit code of `track status` when no session is running
+const STOPPED_EXIT_CODE: u8 = 3;
+
 #[derive(Debug, thiserror::Error)]
 #[error("a CLI error occurred")]
 pub struct CliError;
 
-#[derive(Debug, Clone, Copy, Subcommand)]
+#[derive(Debug, Clone, Subcommand)]
 pub enum Command {
     /// Start tracking time
-    Start,
+    Start {
+        #[command(flatten)]
+        session: SessionArgs,
+        #[command(flatten)]
+        when: WhenArgs,
+    },
+    /// Stop the running session and start a new one without a gap
+    Switch(SessionArgs),
     /// Stop tracking time
-    Stop,
-    /// Show the total tracking time within 24 hours
-    Report,
+    Stop {
+        /// add to the description of the session
+        #[arg(short, long = "message", value_name = "MESSAGE")]
+        message: Option<String>,
+        #[command(flatten)]
+        when: WhenArgs,
+    },
+    /// Record a session after the fact
+    Add {
+        /// when the session started, e.g. `09:15` or `2026-10-17 14:00`
+        #[arg(long, value_name = "TIME")]
+        from: String,
+        /// when the session ended, e.g. `10:00` or `20m ago`
+        #[arg(long, value_name = "TIME")]
+        to: String,
+        #[command(flatten)]
+        session: SessionArgs,
+        /// refuse to add a session overlapping recorded ones
+        #[arg(long)]
+        no_overlap: bool,
+    },
+    /// Discard the running session without recording it
+    Cancel,
+    /// Show the running session, exiting with 3 when stopped
+    Status,
+    /// Take a break without ending the session
+    Pause,
+    /// Continue a paused session
+    Resume,
+    /// Replace the description of the running session
+    Note {
+        /// new description
+        message: String,
+    },
+    /// Show the total tracking time, within the last 24 hours by default
+    Report {
+        /// only include records of this project
+        #[arg(short, long)]
+        project: Option<String>,
+        /// only include records with this tag, can be repeated
+        #[arg(short, long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// break the total down, e.g. `--by day,project` for each project per day
+        #[arg(long, value_enum, value_delimiter = ',', conflicts_with = "notes")]
+        by: Vec<Grouping>,
+        /// list the description of each session instead
+        #[arg(long)]
+        notes: bool,
+        /// leave out the running session
+        #[arg(long)]
+        no_running: bool,
+        /// how to print the report; CSV and TSV hold the time per group,
+        /// only JSON also lists the records
+        #[arg(long, value_enum, default_value_t, conflicts_with = "notes")]
+        output: Output,
+        /// how to write durations
+        #[arg(
+            long,
+            value_enum,
+            value_name = "FORMAT",
+            env = "TRACK_DURATION_FORMAT",
+            default_value_t
+        )]
+        duration_format: DurationFormat,
+        #[command(flatten)]
+        span: SpanArgs,
+        #[command(flatten)]
+        rounding: RoundingArgs,
+    },
+    /// Change a recorded session
+    Edit {
+        /// identifier of the record, as listed by `track log`
+        #[arg(required_unless_present = "interactive")]
+        id: Option<RecordId>,
+        /// new start time, e.g. `09:15` or `2026-10-17 14:00`
+        #[arg(long, value_name = "TIME")]
+        start: Option<String>,
+        /// new end time, e.g. `10:00` or `20m ago`
+        #[arg(long, value_name = "TIME")]
+        end: Option<String>,
+        /// new project, an empty one removes it
+        #[arg(short, long)]
+        project: Option<String>,
+        /// new description, an empty one removes it
+        #[arg(short, long = "message", value_name = "MESSAGE")]
+        message: Option<String>,
+        /// edit the records of a range in $EDITOR instead
+        #[arg(
+            short,
+            long,
+            conflicts_with_all = ["id", "start", "end", "project", "message"]
+        )]
+        interactive: bool,
+        /// first record to edit interactively, 24 hours ago by default
+        #[arg(long, value_name = "TIME", requires = "interactive")]
+        since: Option<String>,
+        /// edit interactively only records starting before this time
+        #[arg(long, value_name = "TIME", requires = "interactive")]
+        until: Option<String>,
+    },
+    /// Remove a recorded session
+    Delete {
+        /// identifier of the record, as listed by `track log`
+        id: RecordId,
+    },
+    /// Revert the last command that changed the database (flatfile backend only)
+    Undo,
+    /// Apply the last undone command again (flatfile backend only)
+    Redo,
+    /// List records with their identifiers, oldest first
+    Log {
+        /// only include records starting at or after this time
+        #[arg(long, value_name = "TIME")]
+        since: Option<String>,
+        /// only include records starting before this time
+        #[arg(long, value_name = "TIME")]
+        until: Option<String>,
+        /// show only this many of the latest records
+        #[arg(short = 'n', long, value_name = "COUNT")]
+        limit: Option<usize>,
+        /// how to print the records
+        #[arg(long, value_enum, default_value_t)]
+        output: Output,
+    },
+    /// Rewrite the journal as a snapshot (journal backend only)
+    Compact,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct SessionArgs {
+    /// project the time is spent on
+    #[arg(short, long)]
+    project: Option<String>,
+    /// tag for the session, can be repeated
+    #[arg(short, long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+    /// describe what the session is about
+    #[arg(short, long = "message", value_name = "MESSAGE")]
+    message: Option<String>,
+}
+
+impl From<SessionArgs> for SessionDetails {
+    fn from(args: SessionArgs) -> Self {
+        Self {
+            project: args.project,
+            tags: args.tags.into_iter().collect(),
+            description: args.message,
+        }
+    }
+}
+
+/// When a session started or stopped, if not now
+#[derive(Debug, Clone, Args)]
+pub struct WhenArgs {
+    /// when it happened, e.g. `09:15`, `2026-10-17 14:00` or `20m ago`
+    #[arg(long, value_name = "TIME", conflicts_with = "ago")]
+    at: Option<String>,
+    /// how long ago it happened, e.g. `20m` or `1h30m`
+    #[arg(long, value_name = "DURATION")]
+    ago: Option<String>,
+}
+
+impl WhenArgs {
+    /// The requested time in UTC, `now` if none was given
+    fn resolve(&self, now: DateTime<Utc>, zone: Zone) -> Result<DateTime<Utc>, CliError> {
+        match (&self.at, &self.ago) {
+            (Some(at), _) => parse_time(at, now, zone).change_context(CliError),
+            (None, Some(ago)) => parse_ago(ago, now).change_context(CliError),
+            (None, None) => Ok(now),
+        }
+    }
+}
+
+/// Which records a report covers
+#[derive(Debug, Clone, Args)]
+pub struct SpanArgs {
+    /// report on today
+    #[arg(long, group = "span")]
+    today: bool,
+    /// report on yesterday
+    #[arg(long, group = "span")]
+    yesterday: bool,
+    /// report on this week
+    #[arg(long, group = "span")]
+    week: bool,
+    /// report on last week
+    #[arg(long, group = "span")]
+    last_week: bool,
+    /// report on this month
+    #[arg(long, group = "span")]
+    month: bool,
+    /// report on records starting at or after this time, e.g. `2026-10-01`
+    #[arg(long, value_name = "TIME", group = "span")]
+    from: Option<String>,
+    /// report on records starting before this time, now by default
+    #[arg(long, value_name = "TIME", requires = "from")]
+    to: Option<String>,
+    /// first day of the week
+    #[arg(
+        long,
+        value_name = "DAY",
+        env = "TRACK_WEEK_START",
+        default_value = "monday"
+    )]
+    week_start: Weekday,
+}
+
+impl SpanArgs {
+    /// The requested timespan, the last 24 hours if none was given
+    fn timespan(&self, now: DateTime<Utc>, zone: Zone) -> Result<ReportTimespan, CliError> {
+        const TWENTY_FOUR_HOURS: u64 = 60 * 60 * 24;
+
+        if let Some(from) = &self.from {
+            let from = parse_time(from, now, zone)
+                .change_context(CliError)
+                .attach_printable("failed to read --from")?;
+            let to = read_time(self.to.clone(), now, zone)
+                .attach_printable("failed to read --to")?
+                .unwrap_or(now);
+            if to < from {
+                return Err(CliError)
+                    .attach_printable("--to is before --from")
+                    .attach(Suggestion("pick a --to time after --from"));
+            }
+            return Ok(ReportTimespan::Range { from, to });
+        }
+
+        Ok(if self.today {
+            ReportTimespan::Today
+        } else if self.yesterday {
+            ReportTimespan::Yesterday
+        } else if self.week {
+            ReportTimespan::ThisWeek
+        } else if self.last_week {
+            ReportTimespan::LastWeek
+        } else if self.month {
+            ReportTimespan::ThisMonth
+        } else {
+            ReportTimespan::Last(Duration::from_secs(TWENTY_FOUR_HOURS))
+        })
+    }
+}
+
+/// How billable time is rounded in a report
+#[derive(Debug, Clone, Args)]
+pub struct RoundingArgs {
+    /// show billable time, rounded this way
+    #[arg(long, value_enum, value_name = "DIRECTION", conflicts_with = "notes")]
+    round: Option<RoundDirection>,
+    /// minutes to round to
+    #[arg(
+        long,
+        value_enum,
+        value_name = "MINUTES",
+        default_value = "15",
+        requires = "round"
+    )]
+    round_to: Increment,
+    /// round each record, or the total of each group
+    #[arg(
+        long,
+        value_enum,
+        value_name = "SCOPE",
+        default_value = "record",
+        requires = "round"
+    )]
+    round_per: RoundScope,
+    /// least billable time per record, e.g. `15m`
+    #[arg(long, value_name = "DURATION", conflicts_with = "notes")]
+    min_billable: Option<String>,
+}
+
+impl RoundingArgs {
+    /// The requested rounding, `None` if billable time wasn't asked for
+    fn rounding(&self) -> Result<Option<Rounding>, CliError> {
+        let minimum = self
+            .min_billable
+            .as_deref()
+            .map(parse_duration)
+            .transpose()
+            .change_context(CliError)
+            .attach_printable("failed to read --min-billable")?;
+        if self.round.is_none() && minimum.is_none() {
+            return Ok(None);
+        }
+
+        let mut rounding = match self.round {
+            Some(direction) => Rounding::new(self.round_to.duration())
+                .with_direction(direction.into())
+                .with_scope(self.round_per.into()),
+            None => Rounding::default(),
+        };
+        if let Some(minimum) = minimum {
+            rounding = rounding.with_minimum(minimum);
+        }
+        Ok(Some(rounding))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RoundDirection {
+    /// to the closest increment, halfway up
+    Nearest,
+    /// to the next increment
+    Up,
+    /// to the previous increment
+    Down,
+}
+
+impl From<RoundDirection> for rounding::Direction {
+    fn from(direction: RoundDirection) -> Self {
+        match direction {
+            RoundDirection::Nearest => Self::Nearest,
+            RoundDirection::Up => Self::Up,
+            RoundDirection::Down => Self::Down,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Increment {
+    #[value(name = "1")]
+    One,
+    #[value(name = "6")]
+    Six,
+    #[value(name = "15")]
+    Fifteen,
+    #[value(name = "30")]
+    Thirty,
+}
+
+impl Increment {
+    fn duration(self) -> Duration {
+        let minutes = match self {
+            Self::One => 1,
+            Self::Six => 6,
+            Self::Fifteen => 15,
+            Self::Thirty => 30,
+        };
+        Duration::from_secs(minutes * 60)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RoundScope {
+    /// round every record, then add them up
+    Record,
+    /// add up the records, then round the total of every group
+    Group,
+}
+
+impl From<RoundScope> for rounding::Scope {
+    fn from(scope: RoundScope) -> Self {
+        match scope {
+            RoundScope::Record => Self::Record,
+            RoundScope::Group => Self::Group,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Grouping {
+    Day,
+    Week,
+    Month,
+    Project,
+    Tag,
+}
+
+impl From<Grouping> for GroupBy {
+    fn from(grouping: Grouping) -> Self {
+        match grouping {
+            Grouping::Day => Self::Day,
+            Grouping::Week => Self::Week,
+            Grouping::Month => Self::Month,
+            Grouping::Project => Self::Project,
+            Grouping::Tag => Self::Tag,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Output {
+    /// aligned text for reading
+    #[default]
+    Text,
+    /// JSON document
+    Json,
+    /// comma-separated values
+    Csv,
+    /// tab-separated values
+    Tsv,
+}
+
+impl Output {
+    /// The machine-readable format, `None` for text
+    fn format(self) -> Option<Format> {
+        match self {
+            Self::Text => None,
+            Self::Json => Some(Format::Json),
+            Self::Csv => Some(Format::Csv),
+            Self::Tsv => Some(Format::Tsv),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum DurationFormat {
+    /// hours, minutes and seconds, e.g. `07:15:00`
+    #[default]
+    Hms,
+    /// like `hms` with whole days split off, e.g. `1d 07:15:00`
+    Days,
+    /// hours to two decimal places, e.g. `7.25h`
+    Decimal,
+    /// words, e.g. `7 hours 15 minutes`
+    Human,
+    /// ISO 8601, e.g. `PT7H15M`
+    Iso,
+    /// short units, e.g. `7h15m`
+    Compact,
+}
+
+impl DurationFormat {
+    fn formatter(self) -> Box<dyn DurationFormatter> {
+        match self {
+            Self::Hms => Box::new(HourMinSecFormatter::default()),
+            Self::Days => Box::new(HourMinSecFormatter::default().with_days()),
+            Self::Decimal => Box::new(DecimalHoursFormatter),
+            Self::Human => Box::new(HumanFormatter),
+            Self::Iso => Box::new(IsoFormatter),
+            Self::Compact => Box::new(CompactFormatter),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// JSON document next to a lockfile
+    #[default]
+    #[value(name = "flatfile")]
+    FlatFile,
+    /// SQLite database
+    Sqlite,
+    /// Append-only JSON Lines journal next to a lockfile
+    Journal,
+}
+
+impl Backend {
+    /// File name of the database in the default data directory
+    fn default_db_name(self) -> &'static str {
+        match self {
+            Self::FlatFile => "records.json",
+            Self::Sqlite => "records.sqlite3",
+            Self::Journal => "records.jsonl",
+        }
+    }
 }
 
 // track --db-dir PATH --lockfile PATH start
@@ -35,46 +512,523 @@ struct Cli {
     /// path to lockfile
     #[arg(short = 'l', long)]
     pub lockfile: Option<PathBuf>,
+    /// storage backend
+    #[arg(short = 'b', long, value_enum, default_value_t)]
+    pub backend: Backend,
+    /// seconds to wait for another process to release the database
+    #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_LOCK_TIMEOUT.as_secs())]
+    pub lock_timeout: u64,
+    /// timezone to read and show times in, e.g. `Europe/Berlin` or `local`
+    #[arg(long, value_name = "ZONE", env = "TRACK_TZ")]
+    pub tz: Option<String>,
+    /// path to config file
+    #[arg(long, value_name = "PATH", env = "TRACK_CONFIG")]
+    pub config: Option<PathBuf>,
     #[command(subcommand)]
     pub command: Command,
 }
 
-pub fn run() -> Result<(), CliError> {
+pub fn run() -> Result<ExitCode, CliError> {
     let args = Cli::parse();
 
-    let db_dir = flatfile_db_path(&args)?;
-    let lockfile = lockfile_path(&args)?;
+    let db_dir = db_path(&args)?;
+    let lock_timeout = Duration::from_secs(args.lock_timeout);
+    let zone = zone(&args)?;
+
+    match args.backend {
+        Backend::FlatFile => {
+            let lockfile = lockfile_path(&args)?;
+            let mut tracker =
+                FlatFileTracker::new(db_dir, lockfile).with_lock_timeout(lock_timeout);
+            match args.command {
+                Command::Undo => {
+                    let undone = tracker
+                        .undo()
+                        .change_context(CliError)
+                        .attach_printable("failed to undo")?;
+                    match undone {
+                        Some(command) => println!("undid {command}"),
+                        None => println!("nothing to undo"),
+                    }
+                    Ok(ExitCode::SUCCESS)
+                }
+                Command::Redo => {
+                    let redone = tracker
+                        .redo()
+                        .change_context(CliError)
+                        .attach_printable("failed to redo")?;
+                    match redone {
+                        Some(command) => println!("redid {command}"),
+                        None => println!("nothing to redo"),
+                    }
+                    Ok(ExitCode::SUCCESS)
+                }
+                command => run_command(&mut tracker, command, zone),
+            }
+        }
+        Backend::Sqlite => {
+            let mut tracker = SqliteTracker::new(db_dir).with_lock_timeout(lock_timeout);
+            run_command(&mut tracker, args.command, zone)
+        }
+        Backend::Journal => {
+            let lockfile = lockfile_path(&args)?;
+            let mut tracker = JournalTracker::new(db_dir, lockfile).with_lock_timeout(lock_timeout);
+            match args.command {
+                Command::Compact => tracker
+                    .compact()
+                    .map(|()| ExitCode::SUCCESS)
+                    .change_context(CliError)
+                    .attach_printable("failed to compact journal"),
+                command => run_command(&mut tracker, command, zone),
+            }
+        }
+    }
+}
 
-    let mut tracker = FlatFileTracker::new(db_dir, lockfile);
+fn run_command<T>(tracker: &mut T, command: Command, zone: Zone) -> Result<ExitCode, CliError>
+where
+    T: Tracker + Reporter,
+{
+    match command {
+        Command::Start { session, when } => {
+            let start = when
+                .resolve(Utc::now(), zone)
+                .attach_printable("failed to read start time")?;
+            match tracker.start_at(StartTime::at(start), session.into()) {
+                Ok(StartupStatus::Started) => (),
+                Ok(StartupStatus::Running) => println!("tracker already running"),
+                Err(e) => return Err(e).change_context(CliError),
+            }
+        }
+        Command::Switch(session) => tracker
+            .switch(session.into())
+            .change_context(CliError)
+            .attach_printable("failed to switch session")?,
+        Command::Stop { message, when } => {
+            let end = when
+                .resolve(Utc::now(), zone)
+                .attach_printable("failed to read stop time")?;
+            tracker
+                .stop_at(EndTime::at(end), message)
+                .change_context(CliError)
+                .attach_printable("failed to stop tracking")?
+        }
+        Command::Add {
+            from,
+            to,
+            session,
+            no_overlap,
+        } => {
+            let now = Utc::now();
+            let start = parse_time(&from, now, zone)
+                .change_context(CliError)
+                .attach_printable("failed to read start time")?;
+            let end = parse_time(&to, now, zone)
+                .change_context(CliError)
+                .attach_printable("failed to read end time")?;
+            let overlap = if no_overlap {
+                Overlap::Refuse
+            } else {
+                Overlap::Allow
+            };
 
-    match args.command {
-        Command::Start => match tracker.start() {
-            Ok(StartupStatus::Started) => (),
-            Ok(StartupStatus::Running) => println!("tracker already running"),
-            Err(e) => return Err(e).change_context(CliError),
+            let record = TimeRecord::new(StartTime::at(start), EndTime::at(end), session.into());
+            let overlapping = tracker
+                .add_record(record, overlap)
+                .change_context(CliError)
+                .attach_printable("failed to add record")?;
+            if !overlapping.is_empty() {
+                eprintln!(
+                    "warning: the record overlaps {} session(s)",
+                    overlapping.len()
+                );
+            }
+        }
+        Command::Cancel => {
+            let discarded = tracker
+                .cancel()
+                .change_context(CliError)
+                .attach_printable("failed to cancel tracking")?;
+            match discarded {
+                Some(duration) => println!(
+                    "discarded {}",
+                    HourMinSecFormatter::default().format(duration)
+                ),
+                None => println!("tracker not running"),
+            }
+        }
+        Command::Status => {
+            let session = tracker
+                .current_session()
+                .change_context(CliError)
+                .attach_printable("failed to get running session")?;
+            let Some(session) = session else {
+                println!("stopped");
+                return Ok(ExitCode::from(STOPPED_EXIT_CODE));
+            };
+            print_session(&session, Utc::now(), zone);
+        }
+        Command::Pause => match tracker.pause() {
+            Ok(PauseStatus::Paused) => (),
+            Ok(PauseStatus::AlreadyPaused) => println!("tracker already paused"),
+            Err(e) => {
+                return Err(e)
+                    .change_context(CliError)
+                    .attach_printable("failed to pause tracking")
+            }
         },
-        Command::Stop => tracker
-            .stop()
+        Command::Resume => match tracker.resume() {
+            Ok(ResumeStatus::Resumed) => (),
+            Ok(ResumeStatus::NotPaused) => println!("tracker not paused"),
+            Err(e) => {
+                return Err(e)
+                    .change_context(CliError)
+                    .attach_printable("failed to resume tracking")
+            }
+        },
+        Command::Note { message } => tracker
+            .set_description(message)
             .change_context(CliError)
-            .attach_printable("failed to stop tracking")?,
-        Command::Report => {
-            let twenty_four_hours = {
-                const TWENTY_FOUR_HOURS: u64 = 60 * 60 * 24;
-                Duration::from_secs(TWENTY_FOUR_HOURS)
+            .attach_printable("failed to update description")?,
+        Command::Report {
+            project,
+            tags,
+            by,
+            notes,
+            no_running,
+            output,
+            duration_format,
+            span,
+            rounding,
+        } => {
+            let timespan = span.timespan(Utc::now(), zone)?;
+            let mut query = ReportQuery::new(timespan)
+                .with_zone(zone)
+                .with_week_start(span.week_start)
+                .with_running(!no_running);
+            if let Some(project) = project {
+                query = query.with_project(project);
+            }
+            for tag in tags {
+                query = query.with_tag(tag);
+            }
+
+            let formatter = duration_format.formatter();
+            if notes {
+                let records = tracker
+                    .matching_records(&query)
+                    .change_context(CliError)
+                    .attach_printable("failed to get records")?;
+                for record in &records {
+                    if let Some(description) = record.description() {
+                        let start = zone.local_time(record.start());
+                        println!(
+                            "{}  {}  {description}{}",
+                            start.format("%Y-%m-%d %H:%M"),
+                            formatter.format(record.duration()),
+                            running_marker(record.is_running())
+                        );
+                    }
+                }
+            } else {
+                let groupings: Vec<GroupBy> = by.into_iter().map(GroupBy::from).collect();
+                let mut report = tracker
+                    .report(&query, &groupings)
+                    .change_context(CliError)
+                    .attach_printable("failed to calculate durations")?;
+                if let Some(rounding) = rounding.rounding()? {
+                    rounding.apply(&mut report.breakdown);
+                }
+                let breakdown = &report.breakdown;
+                if let Some(format) = output.format() {
+                    export::write_report(
+                        std::io::stdout().lock(),
+                        &report,
+                        &groupings,
+                        format,
+                        zone,
+                        &formatter,
+                    )
+                    .change_context(CliError)?;
+                } else if groupings.is_empty() {
+                    println!(
+                        "{}{}{}",
+                        formatter.format(breakdown.total),
+                        billed(breakdown.rounded, &formatter),
+                        running_marker(breakdown.running)
+                    );
+                } else {
+                    print_breakdown(breakdown, &formatter);
+                }
+            }
+        }
+        Command::Edit {
+            interactive: true,
+            since,
+            until,
+            ..
+        } => {
+            let now = Utc::now();
+            let since = read_time(since, now, zone)
+                .attach_printable("failed to read --since")?
+                .unwrap_or(now - TimeDelta::days(1));
+            let until = read_time(until, now, zone).attach_printable("failed to read --until")?;
+            let records = records_between(tracker, Some(since), until)?;
+
+            let changes = editor::edit_records(&records, zone)
+                .change_context(CliError)
+                .attach_printable("failed to edit records")?;
+            match changes {
+                None => println!("edit aborted"),
+                Some(changes) if changes.is_empty() => println!("nothing changed"),
+                Some(changes) => apply_changes(tracker, changes)?,
+            }
+        }
+        Command::Edit {
+            id,
+            start,
+            end,
+            project,
+            message,
+            ..
+        } => {
+            let now = Utc::now();
+            let id = id
+                .ok_or(CliError)
+                .attach_printable("no record to edit")
+                .attach(Suggestion("pass the identifier listed by `track log`"))?;
+            let edit = RecordEdit {
+                start: read_time(start, now, zone)
+                    .attach_printable("failed to read start time")?
+                    .map(StartTime::at),
+                end: read_time(end, now, zone)
+                    .attach_printable("failed to read end time")?
+                    .map(EndTime::at),
+                project: project.map(non_empty),
+                tags: None,
+                description: message.map(non_empty),
             };
-            let total_duration = tracker
-                .total_duration(ReportTimespan::Last(twenty_four_hours))
+
+            let record = tracker
+                .edit_record(id, edit)
+                .change_context(CliError)
+                .attach_printable("failed to edit record")?;
+            print_record(&record, zone);
+        }
+        Command::Delete { id } => {
+            tracker
+                .delete_record(id)
                 .change_context(CliError)
-                .attach_printable("failed to calculate total duration")?;
-            let formatter = HourMinSecFormatter::default();
-            println!("{}", formatter.format(total_duration));
+                .attach_printable("failed to delete record")?;
+        }
+        Command::Log {
+            since,
+            until,
+            limit,
+            output,
+        } => {
+            let now = Utc::now();
+            let since = read_time(since, now, zone).attach_printable("failed to read --since")?;
+            let until = read_time(until, now, zone).attach_printable("failed to read --until")?;
+
+            let mut records = records_between(tracker, since, until)?;
+            if let Some(limit) = limit {
+                records.drain(..records.len().saturating_sub(limit));
+            }
+
+            if let Some(format) = output.format() {
+                let formatter = HourMinSecFormatter::default();
+                export::write_records(std::io::stdout().lock(), &records, format, zone, &formatter)
+                    .change_context(CliError)?;
+            } else {
+                for record in &records {
+                    print_record(record, zone);
+                }
+            }
+        }
+        Command::Undo | Command::Redo => {
+            return Err(CliError)
+                .attach_printable("only the flatfile backend keeps an undo history")
+                .attach(Suggestion("use `--backend flatfile` to undo commands"));
+        }
+        Command::Compact => {
+            return Err(CliError)
+                .attach_printable("only the journal backend can be compacted")
+                .attach(Suggestion("use `--backend journal` to compact a journal"));
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Prints a report breakdown as a table of groups, subgroups and the total
+fn print_breakdown<F>(breakdown: &Breakdown, formatter: &F)
+where
+    F: DurationFormatter,
+{
+    fn rows<F>(
+        groups: &BTreeMap<GroupKey, Breakdown>,
+        depth: usize,
+        formatter: &F,
+        table: &mut Vec<(String, String, String, bool)>,
+    ) where
+        F: DurationFormatter,
+    {
+        for (key, group) in groups {
+            let label = format!("{:indent$}{key}", "", indent = depth * 2);
+            table.push((
+                label,
+                formatter.format(group.total),
+                billed(group.rounded, formatter),
+                group.running,
+            ));
+            rows(&group.groups, depth + 1, formatter, table);
         }
     }
 
+    let mut table = Vec::new();
+    rows(&breakdown.groups, 0, formatter, &mut table);
+    table.push((
+        "total".to_string(),
+        formatter.format(breakdown.total),
+        billed(breakdown.rounded, formatter),
+        breakdown.running,
+    ));
+
+    let width = |column: fn(&(String, String, String, bool)) -> &String| {
+        table
+            .iter()
+            .map(|row| column(row).chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+    let (label_width, duration_width) = (width(|row| &row.0), width(|row| &row.1));
+    for (label, duration, billed, running) in &table {
+        let line = format!(
+            "{label:<label_width$}  {duration:<duration_width$}{billed}{}",
+            running_marker(*running)
+        );
+        println!("{}", line.trim_end());
+    }
+}
+
+/// Shows the billable time next to the time spent, if rounding applies
+fn billed<F>(rounded: Option<Duration>, formatter: &F) -> String
+where
+    F: DurationFormatter,
+{
+    rounded
+        .map(|rounded| format!("  billed {}", formatter.format(rounded)))
+        .unwrap_or_default()
+}
+
+/// Marks report lines counting the running session
+fn running_marker(running: bool) -> &'static str {
+    if running {
+        "  (running)"
+    } else {
+        ""
+    }
+}
+
+/// Records starting within the range, oldest first
+fn records_between<T>(
+    tracker: &T,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Result<Vec<TimeRecord>, CliError>
+where
+    T: Tracker,
+{
+    let records = match since {
+        Some(since) => tracker.records_since(since).map(Iterator::collect),
+        None => tracker.records().map(Iterator::collect),
+    };
+    let mut records: Vec<_> = records
+        .change_context(CliError)
+        .attach_printable("failed to get records")?;
+    if let Some(until) = until {
+        records.retain(|record| record.start() < until);
+    }
+    records.sort_by_key(TimeRecord::start);
+    Ok(records)
+}
+
+/// Applies what was changed in the editor
+fn apply_changes<T>(tracker: &mut T, changes: RecordChanges) -> Result<(), CliError>
+where
+    T: Tracker,
+{
+    let (edited, deleted, added) = (
+        changes.edits.len(),
+        changes.deletions.len(),
+        changes.additions.len(),
+    );
+    tracker
+        .apply_changes(changes)
+        .change_context(CliError)
+        .attach_printable("failed to apply the edited records")?;
+
+    println!("{edited} edited, {deleted} deleted, {added} added");
     Ok(())
 }
 
-fn flatfile_db_path(args: &Cli) -> Result<PathBuf, CliError> {
+/// Reads an optional `--at`-style time
+fn read_time(
+    time: Option<String>,
+    now: DateTime<Utc>,
+    zone: Zone,
+) -> Result<Option<DateTime<Utc>>, CliError> {
+    time.map(|time| parse_time(&time, now, zone))
+        .transpose()
+        .change_context(CliError)
+}
+
+/// Prints the running session as shown by `track status`
+fn print_session(session: &LockfileData, now: DateTime<Utc>, zone: Zone) {
+    let state = match session.state() {
+        TrackerState::Paused => "paused",
+        _ => "running",
+    };
+    println!("{state}");
+    println!(
+        "started  {}",
+        zone.local_time(session.start()).format("%Y-%m-%d %H:%M:%S")
+    );
+    println!(
+        "elapsed  {}",
+        HourMinSecFormatter::default().format(session.elapsed(now))
+    );
+    if let Some(project) = &session.details.project {
+        println!("project  {project}");
+    }
+    if !session.details.tags.is_empty() {
+        let tags: Vec<_> = session.details.tags.iter().map(String::as_str).collect();
+        println!("tags     {}", tags.join(", "));
+    }
+    if let Some(description) = &session.details.description {
+        println!("note     {description}");
+    }
+}
+
+/// Prints a record as a line of `track log`
+fn print_record(record: &TimeRecord, zone: Zone) {
+    println!(
+        "{}  {}  {}  {}  {}",
+        record.id(),
+        zone.local_time(record.start()).format("%Y-%m-%d %H:%M"),
+        zone.local_time(record.end()).format("%Y-%m-%d %H:%M"),
+        HourMinSecFormatter::default().format(record.duration()),
+        record.project().unwrap_or("-"),
+    );
+}
+
+/// `None` for an empty value, which removes it
+fn non_empty(value: String) -> Option<String> {
+    (!value.is_empty()).then_some(value)
+}
+
+fn db_path(args: &Cli) -> Result<PathBuf, CliError> {
     match &args.db_dir {
         Some(db_dir) => Ok(db_dir.clone()),
         None => {
@@ -87,12 +1041,45 @@ fn flatfile_db_path(args: &Cli) -> Result<PathBuf, CliError> {
             std::fs::create_dir_all(&db_path)
                 .change_context(CliError)
                 .attach_printable("failed to create 'track' database directory")?;
-            db_path.push("records.json");
+            db_path.push(args.backend.default_db_name());
             Ok(db_path)
         }
     }
 }
 
+/// The timezone from `--tz`, the config or `TZ`, in that order
+fn zone(args: &Cli) -> Result<Zone, CliError> {
+    if let Some(name) = &args.tz {
+        return Zone::parse(name)
+            .change_context(CliError)
+            .attach_printable("failed to read --tz");
+    }
+
+    let path = match &args.config {
+        Some(path) => Some(path.clone()),
+        None => dirs::config_dir().map(|dir| dir.join("track").join("config.toml")),
+    };
+    let timezone = match path {
+        Some(path) => {
+            let config = config::load(&path).change_context(CliError)?;
+            for key in config.unknown_settings() {
+                eprintln!(
+                    "warning: ignoring unknown setting '{key}' in '{}'",
+                    path.display()
+                );
+            }
+            config.timezone
+        }
+        None => None,
+    };
+    match timezone {
+        Some(name) => Zone::parse(&name)
+            .change_context(CliError)
+            .attach_printable("failed to read timezone from config"),
+        None => Ok(Zone::from_env()),
+    }
+}
+
 fn lockfile_path(args: &Cli) -> Result<PathBuf, CliError> {
     match &args.lockfile {
         Some(lockfile) => Ok(lockfile.clone()),
@@ -0,0 +1,69 @@
+//! Settings read from `config.toml`
+//!
+//! The file lives in the `track` directory of the user's configuration
+//! directory, e.g. `~/.config/track/config.toml`. It is optional, and so is
+//! every setting in it; settings it doesn't know are ignored with a warning.
+//!
+//! `--tz` and `TRACK_TZ` take precedence over `timezone`, which in turn
+//! takes precedence over `TZ`.
+
+use std::path::Path;
+
+use error_stack::{Result, ResultExt};
+use serde::Deserialize;
+
+use crate::error::Suggestion;
+
+#[derive(Debug, thiserror::Error)]
+#[error("config error")]
+pub struct ConfigError;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    /// IANA name of the timezone times are read and shown in, or `local`
+    pub timezone: Option<String>,
+    /// Settings of newer versions, or typos
+    #[serde(flatten)]
+    unknown: toml::Table,
+}
+
+impl Config {
+    /// Names of the settings this version doesn't know, to warn about
+    pub fn unknown_settings(&self) -> impl Iterator<Item = &str> {
+        self.unknown.keys().map(String::as_str)
+    }
+}
+
+/// Reads the config at `path`, the defaults if there is none
+pub fn load(path: &Path) -> Result<Config, ConfigError> {
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let buf = std::fs::read_to_string(path)
+        .change_context(ConfigError)
+        .attach_printable_lazy(|| format!("failed to read config '{}'", path.display()))?;
+    toml::from_str(&buf)
+        .change_context(ConfigError)
+        .attach_printable_lazy(|| format!("failed to parse config '{}'", path.display()))
+        .attach(Suggestion("check the config file for typos"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_config_has_defaults() {
+        let config = load(Path::new("/nonexistent/track/config.toml")).unwrap();
+
+        assert!(config.timezone.is_none());
+    }
+
+    #[test]
+    fn unknown_settings_are_set_aside() {
+        let config = toml::from_str::<Config>("time_zone = \"Europe/Berlin\"\n").unwrap();
+
+        assert!(config.timezone.is_none());
+        assert_eq!(config.unknown_settings().collect::<Vec<_>>(), ["time_zone"]);
+    }
+}
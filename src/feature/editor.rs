@@ -0,0 +1,397 @@
+//! Editing records as TOML in the user's editor
+//!
+//! The records are rendered into a temporary file and `$VISUAL` or `$EDITOR`
+//! is opened on it. Once the editor exits the file is read back and compared
+//! with the records, yielding the edits, deletions and additions to apply.
+//! A buffer that doesn't parse or validate is annotated with the problem and
+//! opened again, until it is fixed or emptied.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs::OpenOptions,
+    io::Write,
+    path::Path,
+    process::Command,
+};
+
+use chrono::{DateTime, Utc};
+use error_stack::{Result, ResultExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::time::{parse_time, Zone},
+    feature::tracker::{
+        EndTime, RecordChanges, RecordEdit, RecordId, SessionDetails, StartTime, TimeRecord,
+    },
+};
+
+/// How times are written in the buffer
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Prefix of the lines describing why the buffer was reopened
+const PROBLEM_PREFIX: &str = "# error: ";
+
+#[derive(Debug, thiserror::Error)]
+#[error("editor error")]
+pub struct EditorError;
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct Buffer {
+    #[serde(default, rename = "record")]
+    records: Vec<Entry>,
+}
+
+/// A record as written in the buffer
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct Entry {
+    /// Missing for records added in the editor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<RecordId>,
+    start: String,
+    end: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    tags: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+}
+
+impl Entry {
+    fn from_record(record: &TimeRecord, zone: Zone) -> Self {
+        let format = |time| zone.local_time(time).format(TIME_FORMAT).to_string();
+        Self {
+            id: Some(record.id()),
+            start: format(record.start()),
+            end: format(record.end()),
+            project: record.project().map(str::to_string),
+            tags: record.tags().clone(),
+            description: record.description().map(str::to_string),
+        }
+    }
+}
+
+/// Removes the temporary buffer once editing is over
+/// Lets the user edit `records` in their editor
+///
+/// Returns `None` if the user aborted by emptying the buffer.
+pub fn edit_records(
+    records: &[TimeRecord],
+    zone: Zone,
+) -> Result<Option<RecordChanges>, EditorError> {
+    // a directory only we can enter, so nobody can put a file or link in our way
+    let dir = tempfile::Builder::new()
+        .prefix("track-edit-")
+        .tempdir()
+        .change_context(EditorError)
+        .attach_printable("failed to create directory for edit buffer")?;
+    let buffer = dir.path().join("records.toml");
+
+    let mut text = render(records, zone)?;
+    loop {
+        write_buffer(&buffer, &text)?;
+        launch_editor(&buffer)?;
+        text = std::fs::read_to_string(&buffer)
+            .change_context(EditorError)
+            .attach_printable("failed to read edit buffer")?;
+
+        if is_blank(&text) {
+            return Ok(None);
+        }
+        match read_buffer(&text, records, Utc::now(), zone) {
+            Ok(changes) => return Ok(Some(changes)),
+            Err(problem) => text = annotate(&text, &problem),
+        }
+    }
+}
+
+/// Writes `text` to a new file at `path`, replacing the one the editor left
+fn write_buffer(path: &Path, text: &str) -> Result<(), EditorError> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            return Err(e)
+                .change_context(EditorError)
+                .attach_printable("failed to remove old edit buffer");
+        }
+        _ => {}
+    }
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .change_context(EditorError)
+        .attach_printable("failed to create edit buffer")?;
+    file.write_all(text.as_bytes())
+        .change_context(EditorError)
+        .attach_printable("failed to write edit buffer")
+}
+
+fn render(records: &[TimeRecord], zone: Zone) -> Result<String, EditorError> {
+    let buffer = Buffer {
+        records: records
+            .iter()
+            .map(|record| Entry::from_record(record, zone))
+            .collect(),
+    };
+    let body = toml::to_string(&buffer)
+        .change_context(EditorError)
+        .attach_printable("failed to render records")?;
+
+    Ok(format!(
+        "# Edit the records below, then save and close the editor.\n\
+         # Remove a [[record]] to delete it, add one without an id to create it.\n\
+         # Times are written as YYYY-MM-DD HH:MM in the {zone} timezone.\n\
+         # Empty the file to abort.\n\n{body}"
+    ))
+}
+
+/// Opens `path` in `$VISUAL` or `$EDITOR`, falling back to `vi`
+fn launch_editor(path: &Path) -> Result<(), EditorError> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    // run through the shell like git does, so the editor may carry arguments
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg("sh")
+        .arg(path)
+        .status()
+        .change_context(EditorError)
+        .attach_printable_lazy(|| format!("failed to run editor '{editor}'"))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(EditorError).attach_printable(format!("editor '{editor}' exited with {status}"))
+    }
+}
+
+/// Whether the buffer holds nothing but comments
+fn is_blank(text: &str) -> bool {
+    text.lines()
+        .map(str::trim)
+        .all(|line| line.is_empty() || line.starts_with('#'))
+}
+
+/// Puts `problem` at the top of the buffer, replacing any earlier one
+fn annotate(text: &str, problem: &str) -> String {
+    let mut annotated = problem
+        .lines()
+        .map(|line| format!("{PROBLEM_PREFIX}{line}\n"))
+        .collect::<String>();
+    for line in text
+        .lines()
+        .filter(|line| !line.starts_with(PROBLEM_PREFIX))
+    {
+        annotated.push_str(line);
+        annotated.push('\n');
+    }
+    annotated
+}
+
+/// Compares the edited buffer with the `original` records
+///
+/// The error describes the problem for the user to fix.
+fn read_buffer(
+    text: &str,
+    original: &[TimeRecord],
+    now: DateTime<Utc>,
+    zone: Zone,
+) -> std::result::Result<RecordChanges, String> {
+    let buffer = toml::from_str::<Buffer>(text).map_err(|e| e.to_string())?;
+
+    let mut changes = RecordChanges::default();
+    let mut seen = HashSet::new();
+    let mut spans = Vec::new();
+    for (position, entry) in buffer.records.into_iter().enumerate() {
+        let name = format!("record {}", position + 1);
+        let before = match entry.id {
+            Some(id) => {
+                if !seen.insert(id) {
+                    return Err(format!("{name}: {id} appears more than once"));
+                }
+                let record = original
+                    .iter()
+                    .find(|record| record.id() == id)
+                    .ok_or_else(|| format!("{name}: {id} is not one of the edited records"))?;
+                Some((record, Entry::from_record(record, zone)))
+            }
+            None => None,
+        };
+
+        // unchanged times keep their full precision
+        let time = |field: &str, written: &str, unchanged: Option<DateTime<Utc>>| {
+            unchanged.map_or_else(
+                || {
+                    parse_time(written, now, zone)
+                        .map_err(|_| format!("{name}: invalid {field} time '{written}'"))
+                },
+                Ok,
+            )
+        };
+        let start = time(
+            "start",
+            &entry.start,
+            before
+                .as_ref()
+                .filter(|(_, rendered)| rendered.start == entry.start)
+                .map(|(record, _)| record.start()),
+        )?;
+        let end = time(
+            "end",
+            &entry.end,
+            before
+                .as_ref()
+                .filter(|(_, rendered)| rendered.end == entry.end)
+                .map(|(record, _)| record.end()),
+        )?;
+        if end < start {
+            return Err(format!("{name}: ends before it starts"));
+        }
+        spans.push((start, end, name));
+
+        match before {
+            Some((_, rendered)) if rendered == entry => (),
+            Some((record, rendered)) => {
+                let edit = RecordEdit {
+                    start: (rendered.start != entry.start).then_some(StartTime::at(start)),
+                    end: (rendered.end != entry.end).then_some(EndTime::at(end)),
+                    project: (rendered.project != entry.project).then_some(entry.project),
+                    tags: (rendered.tags != entry.tags).then_some(entry.tags),
+                    description: (rendered.description != entry.description)
+                        .then_some(entry.description),
+                };
+                changes.edits.push((record.id(), edit));
+            }
+            None => {
+                let details = SessionDetails {
+                    project: entry.project,
+                    tags: entry.tags,
+                    description: entry.description,
+                };
+                changes.additions.push(TimeRecord::new(
+                    StartTime::at(start),
+                    EndTime::at(end),
+                    details,
+                ));
+            }
+        }
+    }
+
+    spans.sort_by_key(|(start, _, _)| *start);
+    for pair in spans.windows(2) {
+        let ((_, end, first), (start, _, second)) = (&pair[0], &pair[1]);
+        if end > start {
+            return Err(format!("{first} and {second} overlap"));
+        }
+    }
+
+    changes.deletions = original
+        .iter()
+        .map(TimeRecord::id)
+        .filter(|id| !seen.contains(id))
+        .collect();
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn utc(h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 17, h, m, 0).unwrap()
+    }
+
+    fn record(start: DateTime<Utc>, end: DateTime<Utc>, project: &str) -> TimeRecord {
+        let details = SessionDetails {
+            project: Some(project.to_string()),
+            ..Default::default()
+        };
+        TimeRecord::new(StartTime::at(start), EndTime::at(end), details)
+    }
+
+    fn zone() -> Zone {
+        Zone::Named(chrono_tz::UTC)
+    }
+
+    #[test]
+    fn unchanged_buffer_has_no_changes() {
+        let records = vec![record(utc(9, 0), utc(10, 0), "acme")];
+        let text = render(&records, zone()).unwrap();
+
+        let changes = read_buffer(&text, &records, utc(12, 0), zone()).unwrap();
+
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn only_changed_fields_are_edited() {
+        let records = vec![record(utc(9, 0), utc(10, 0), "acme")];
+        let text = render(&records, zone())
+            .unwrap()
+            .replace("acme", "initech")
+            .replace("10:00", "10:30");
+
+        let changes = read_buffer(&text, &records, utc(12, 0), zone()).unwrap();
+
+        let (id, edit) = &changes.edits[0];
+        assert_eq!(*id, records[0].id());
+        assert!(edit.start.is_none());
+        assert_eq!(edit.project, Some(Some("initech".to_string())));
+        assert!(edit.end.is_some());
+    }
+
+    #[test]
+    fn removed_and_new_records_are_deleted_and_added() {
+        let records = vec![record(utc(9, 0), utc(10, 0), "acme")];
+        let text = "[[record]]\nstart = \"2026-10-17 11:00\"\nend = \"2026-10-17 12:00\"\n";
+
+        let changes = read_buffer(text, &records, utc(12, 0), zone()).unwrap();
+
+        assert_eq!(changes.deletions, vec![records[0].id()]);
+        assert_eq!(changes.additions.len(), 1);
+    }
+
+    #[test]
+    fn overlapping_records_are_a_problem() {
+        let records = vec![
+            record(utc(9, 0), utc(10, 0), "acme"),
+            record(utc(10, 0), utc(11, 0), "initech"),
+        ];
+        let text =
+            render(&records, zone())
+                .unwrap()
+                .replacen("2026-10-17 10:00", "2026-10-17 10:15", 1);
+
+        let problem = read_buffer(&text, &records, utc(12, 0), zone()).unwrap_err();
+
+        assert_eq!(problem, "record 1 and record 2 overlap");
+    }
+
+    #[test]
+    fn malformed_time_is_a_problem() {
+        let records = vec![record(utc(9, 0), utc(10, 0), "acme")];
+        let text = render(&records, zone())
+            .unwrap()
+            .replace("2026-10-17 10:00", "tomorrow-ish");
+
+        let problem = read_buffer(&text, &records, utc(12, 0), zone()).unwrap_err();
+
+        assert_eq!(problem, "record 1: invalid end time 'tomorrow-ish'");
+    }
+
+    #[test]
+    fn annotation_replaces_earlier_problem() {
+        let text = annotate("[[record]]\n", "first");
+
+        let text = annotate(&text, "second");
+
+        assert_eq!(text, "# error: second\n[[record]]\n");
+    }
+}
@@ -0,0 +1,522 @@
+//! Machine-readable output of reports and records
+//!
+//! Reports and records are written as JSON documents for scripts, or as CSV
+//! and TSV tables for spreadsheets. Durations are given both in seconds and
+//! in the form the text output shows them in, times as RFC 3339 in the
+//! user's timezone.
+
+use std::{collections::BTreeMap, io::Write, time::Duration};
+
+use chrono::{DateTime, FixedOffset};
+use error_stack::{Result, ResultExt};
+use serde::Serialize;
+
+use crate::{
+    common::time::Zone,
+    feature::{
+        report_fmt::DurationFormatter,
+        tracker::{Breakdown, GroupBy, GroupKey, RecordId, Report, TimeRecord},
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+#[error("export error")]
+pub struct ExportError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+    Tsv,
+}
+
+#[derive(Debug, Serialize)]
+struct DurationOut {
+    seconds: u64,
+    formatted: String,
+}
+
+impl DurationOut {
+    fn new<F>(duration: Duration, formatter: &F) -> Self
+    where
+        F: DurationFormatter,
+    {
+        Self {
+            seconds: duration.as_secs(),
+            formatted: formatter.format(duration),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RecordOut<'a> {
+    /// Missing for the running session
+    id: Option<RecordId>,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    duration: DurationOut,
+    project: Option<&'a str>,
+    tags: Vec<&'a str>,
+    description: Option<&'a str>,
+    running: bool,
+}
+
+impl<'a> RecordOut<'a> {
+    fn new<F>(record: &'a TimeRecord, zone: Zone, formatter: &F) -> Self
+    where
+        F: DurationFormatter,
+    {
+        Self {
+            id: (!record.is_running()).then(|| record.id()),
+            start: zone.with_offset(record.start()),
+            end: zone.with_offset(record.end()),
+            duration: DurationOut::new(record.duration(), formatter),
+            project: record.project(),
+            tags: record.tags().iter().map(String::as_str).collect(),
+            description: record.description(),
+            running: record.is_running(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct GroupOut {
+    /// Day, week or month as a date, or the project or tag
+    key: Option<String>,
+    total: DurationOut,
+    /// Billable time, if rounding applies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rounded: Option<DurationOut>,
+    running: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    groups: Vec<GroupOut>,
+}
+
+impl GroupOut {
+    fn all<F>(groups: &BTreeMap<GroupKey, Breakdown>, formatter: &F) -> Vec<Self>
+    where
+        F: DurationFormatter,
+    {
+        groups
+            .iter()
+            .map(|(key, group)| Self {
+                key: key_cell(key),
+                total: DurationOut::new(group.total, formatter),
+                rounded: group
+                    .rounded
+                    .map(|rounded| DurationOut::new(rounded, formatter)),
+                running: group.running,
+                groups: Self::all(&group.groups, formatter),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ReportOut<'a> {
+    from: DateTime<FixedOffset>,
+    to: DateTime<FixedOffset>,
+    grouped_by: Vec<&'static str>,
+    total: DurationOut,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rounded: Option<DurationOut>,
+    running: bool,
+    groups: Vec<GroupOut>,
+    records: Vec<RecordOut<'a>>,
+}
+
+/// Writes a report; tables get a row for each innermost group
+///
+/// Tables have columns for the billable time if rounding was applied. Only
+/// JSON lists the records, tables hold the durations per group.
+pub fn write_report<W, F>(
+    out: W,
+    report: &Report,
+    groupings: &[GroupBy],
+    format: Format,
+    zone: Zone,
+    formatter: &F,
+) -> Result<(), ExportError>
+where
+    W: Write,
+    F: DurationFormatter,
+{
+    let delimiter = match format {
+        Format::Json => {
+            let report = ReportOut {
+                from: zone.with_offset(report.from),
+                to: zone.with_offset(report.to),
+                grouped_by: groupings.iter().map(|grouping| grouping.name()).collect(),
+                total: DurationOut::new(report.breakdown.total, formatter),
+                rounded: report
+                    .breakdown
+                    .rounded
+                    .map(|rounded| DurationOut::new(rounded, formatter)),
+                running: report.breakdown.running,
+                groups: GroupOut::all(&report.breakdown.groups, formatter),
+                records: report
+                    .records
+                    .iter()
+                    .map(|record| RecordOut::new(record, zone, formatter))
+                    .collect(),
+            };
+            return write_json(out, &report);
+        }
+        Format::Csv => b',',
+        Format::Tsv => b'\t',
+    };
+
+    let mut table = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(out);
+    let mut header = vec!["from", "to"];
+    header.extend(groupings.iter().map(|grouping| grouping.name()));
+    header.extend(["seconds", "duration"]);
+    if report.breakdown.rounded.is_some() {
+        header.extend(["rounded_seconds", "rounded_duration"]);
+    }
+    header.push("running");
+    write_row(&mut table, header)?;
+
+    let bounds = vec![
+        zone.with_offset(report.from).to_rfc3339(),
+        zone.with_offset(report.to).to_rfc3339(),
+    ];
+    let keys = bounds.len() + groupings.len();
+    write_groups(&mut table, bounds, keys, &report.breakdown, formatter)?;
+    flush(table)
+}
+
+/// Writes the rows of the innermost groups of `breakdown`
+///
+/// Rows start with `keys` cells, left empty where there is no group, as for
+/// the total of a grouped report without records.
+fn write_groups<W, F>(
+    table: &mut csv::Writer<W>,
+    cells: Vec<String>,
+    keys: usize,
+    breakdown: &Breakdown,
+    formatter: &F,
+) -> Result<(), ExportError>
+where
+    W: Write,
+    F: DurationFormatter,
+{
+    if breakdown.groups.is_empty() {
+        let mut row = cells;
+        row.resize(keys, String::new());
+        for duration in [Some(breakdown.total), breakdown.rounded]
+            .into_iter()
+            .flatten()
+        {
+            row.extend([duration.as_secs().to_string(), formatter.format(duration)]);
+        }
+        row.push(breakdown.running.to_string());
+        return write_row(table, row);
+    }
+
+    for (key, group) in &breakdown.groups {
+        let mut row = cells.clone();
+        row.push(key_cell(key).unwrap_or_default());
+        write_groups(table, row, keys, group, formatter)?;
+    }
+    Ok(())
+}
+
+/// Writes records, one row each in tables
+pub fn write_records<W, F>(
+    out: W,
+    records: &[TimeRecord],
+    format: Format,
+    zone: Zone,
+    formatter: &F,
+) -> Result<(), ExportError>
+where
+    W: Write,
+    F: DurationFormatter,
+{
+    let delimiter = match format {
+        Format::Json => {
+            let records: Vec<_> = records
+                .iter()
+                .map(|record| RecordOut::new(record, zone, formatter))
+                .collect();
+            return write_json(out, &records);
+        }
+        Format::Csv => b',',
+        Format::Tsv => b'\t',
+    };
+
+    let mut table = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(out);
+    write_row(
+        &mut table,
+        [
+            "id",
+            "start",
+            "end",
+            "seconds",
+            "duration",
+            "project",
+            "tags",
+            "description",
+            "running",
+        ],
+    )?;
+    for record in records {
+        let RecordOut {
+            id,
+            start,
+            end,
+            duration,
+            project,
+            tags,
+            description,
+            running,
+        } = RecordOut::new(record, zone, formatter);
+        write_row(
+            &mut table,
+            [
+                id.map(|id| id.to_string()).unwrap_or_default(),
+                start.to_rfc3339(),
+                end.to_rfc3339(),
+                duration.seconds.to_string(),
+                duration.formatted,
+                project.unwrap_or_default().to_string(),
+                tags.join(" "),
+                description.unwrap_or_default().to_string(),
+                running.to_string(),
+            ],
+        )?;
+    }
+    flush(table)
+}
+
+/// How a group is named in machine-readable output, `None` for no project or tag
+fn key_cell(key: &GroupKey) -> Option<String> {
+    match key {
+        GroupKey::Period(GroupBy::Month, date) => Some(date.format("%Y-%m").to_string()),
+        GroupKey::Period(_, date) => Some(date.format("%Y-%m-%d").to_string()),
+        GroupKey::Name(name) => name.clone(),
+    }
+}
+
+fn write_json<W, T>(mut out: W, value: &T) -> Result<(), ExportError>
+where
+    W: Write,
+    T: Serialize,
+{
+    serde_json::to_writer_pretty(&mut out, value)
+        .change_context(ExportError)
+        .attach_printable("failed to serialize output")?;
+    writeln!(out)
+        .change_context(ExportError)
+        .attach_printable("failed to write output")
+}
+
+fn write_row<W, I, T>(table: &mut csv::Writer<W>, row: I) -> Result<(), ExportError>
+where
+    W: Write,
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    table
+        .write_record(row)
+        .change_context(ExportError)
+        .attach_printable("failed to write row")
+}
+
+fn flush<W>(mut table: csv::Writer<W>) -> Result<(), ExportError>
+where
+    W: Write,
+{
+    table
+        .flush()
+        .change_context(ExportError)
+        .attach_printable("failed to write output")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::feature::{
+        report_fmt::HourMinSecFormatter,
+        tracker::{EndTime, SessionDetails, StartTime},
+    };
+
+    use super::*;
+
+    fn record(project: &str, description: &str) -> TimeRecord {
+        let start = Utc.with_ymd_and_hms(2026, 10, 17, 9, 0, 0).unwrap();
+        let details = SessionDetails {
+            project: Some(project.to_string()),
+            tags: ["deep", "review"].map(str::to_string).into(),
+            description: Some(description.to_string()),
+        };
+        TimeRecord::new(
+            StartTime::at(start),
+            EndTime::at(start + chrono::TimeDelta::minutes(90)),
+            details,
+        )
+    }
+
+    fn write(records: &[TimeRecord], format: Format) -> String {
+        let mut out = Vec::new();
+        write_records(
+            &mut out,
+            records,
+            format,
+            Zone::Named(chrono_tz::UTC),
+            &HourMinSecFormatter::default(),
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_separators() {
+        let records = [record("acme", "planning, then review")];
+
+        let csv = write(&records, Format::Csv);
+
+        let row = csv.lines().nth(1).unwrap();
+        assert!(row.ends_with(
+            ",2026-10-17T09:00:00+00:00,2026-10-17T10:30:00+00:00,5400,01:30:00,\
+             acme,deep review,\"planning, then review\",false"
+        ));
+    }
+
+    #[test]
+    fn tsv_separates_fields_with_tabs() {
+        let records = [record("acme", "planning")];
+
+        let tsv = write(&records, Format::Tsv);
+
+        let header = tsv.lines().next().unwrap();
+        assert_eq!(
+            header,
+            "id\tstart\tend\tseconds\tduration\tproject\ttags\tdescription\trunning"
+        );
+    }
+
+    #[test]
+    fn json_gives_durations_in_seconds_and_formatted() {
+        let records = [record("acme", "planning")];
+
+        let json: serde_json::Value = serde_json::from_str(&write(&records, Format::Json)).unwrap();
+
+        assert_eq!(json[0]["duration"]["seconds"], 5400);
+        assert_eq!(json[0]["duration"]["formatted"], "01:30:00");
+        assert_eq!(json[0]["tags"], serde_json::json!(["deep", "review"]));
+        assert_eq!(json[0]["id"], records[0].id().to_string());
+    }
+
+    #[test]
+    fn tables_show_rounded_durations() {
+        // Given a report with billable time
+        let records = vec![record("acme", "planning")];
+        let report = Report {
+            from: records[0].start(),
+            to: records[0].end(),
+            breakdown: Breakdown {
+                total: records[0].duration(),
+                rounded: Some(Duration::from_secs(6300)),
+                records: records.clone(),
+                ..Breakdown::default()
+            },
+            records,
+        };
+
+        // When it is written as CSV
+        let mut out = Vec::new();
+        write_report(
+            &mut out,
+            &report,
+            &[],
+            Format::Csv,
+            Zone::Named(chrono_tz::UTC),
+            &HourMinSecFormatter::default(),
+        )
+        .unwrap();
+
+        // Then the rounded duration follows the one spent
+        let csv = String::from_utf8(out).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "from,to,seconds,duration,rounded_seconds,rounded_duration,running"
+        );
+        assert!(lines
+            .next()
+            .unwrap()
+            .ends_with(",5400,01:30:00,6300,01:45:00,false"));
+    }
+
+    fn write_empty_report(groupings: &[GroupBy], format: Format) -> String {
+        let start = Utc.with_ymd_and_hms(2026, 10, 17, 0, 0, 0).unwrap();
+        let report = Report {
+            from: start,
+            to: start + chrono::TimeDelta::days(1),
+            breakdown: Breakdown::default(),
+            records: Vec::new(),
+        };
+        let mut out = Vec::new();
+        write_report(
+            &mut out,
+            &report,
+            groupings,
+            format,
+            Zone::Named(chrono_tz::UTC),
+            &HourMinSecFormatter::default(),
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv_of_empty_grouped_report_has_full_total_row() {
+        let csv = write_empty_report(&[GroupBy::Project], Format::Csv);
+
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "from,to,project,seconds,duration,running"
+        );
+        assert_eq!(
+            lines.next().unwrap(),
+            "2026-10-17T00:00:00+00:00,2026-10-18T00:00:00+00:00,,0,00:00:00,false"
+        );
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn tsv_of_empty_grouped_report_has_full_total_row() {
+        let tsv = write_empty_report(&[GroupBy::Day, GroupBy::Project], Format::Tsv);
+
+        let rows = tsv
+            .lines()
+            .map(|line| line.split('\t').count())
+            .collect::<Vec<_>>();
+        assert_eq!(rows, [7, 7]);
+    }
+
+    #[test]
+    fn times_have_the_offset_of_the_zone() {
+        let records = [record("acme", "planning")];
+        let mut out = Vec::new();
+
+        write_records(
+            &mut out,
+            &records,
+            Format::Json,
+            Zone::Named(chrono_tz::Europe::Berlin),
+            &HourMinSecFormatter::default(),
+        )
+        .unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json[0]["start"], "2026-10-17T11:00:00+02:00");
+    }
+}
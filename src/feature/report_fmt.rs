@@ -1,20 +1,144 @@
 use std::time::Duration;
 
+const SECONDS_PER_MINUTE: u64 = 60;
+const SECONDS_PER_HOUR: u64 = 60 * SECONDS_PER_MINUTE;
+const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR;
+
+/// `03:11:00`, or `1d 03:11:00` with days; hours grow past two digits
+#[derive(Debug, Default)]
+pub struct HourMinSecFormatter {
+    days: bool,
+}
+
+/// `7.25h`, hours to two decimal places
+#[derive(Debug, Default)]
+pub struct DecimalHoursFormatter;
+
+/// `7 hours 15 minutes`, with seconds only below a minute
+#[derive(Debug, Default)]
+pub struct HumanFormatter;
+
+/// `PT7H15M`, an ISO 8601 duration
 #[derive(Debug, Default)]
-pub struct HourMinSecFormatter;
+pub struct IsoFormatter;
+
+/// `7h15m`, with seconds only below a minute
+#[derive(Debug, Default)]
+pub struct CompactFormatter;
 
 pub trait DurationFormatter {
     fn format(&self, duration: Duration) -> String;
 }
 
+impl<F> DurationFormatter for Box<F>
+where
+    F: DurationFormatter + ?Sized,
+{
+    fn format(&self, duration: Duration) -> String {
+        (**self).format(duration)
+    }
+}
+
+impl HourMinSecFormatter {
+    /// Splits off whole days from durations of a day or longer
+    pub fn with_days(mut self) -> Self {
+        self.days = true;
+        self
+    }
+}
+
 impl DurationFormatter for HourMinSecFormatter {
     fn format(&self, duration: Duration) -> String {
-        let duration_in_sec = duration.as_secs();
+        let mut duration_in_sec = duration.as_secs();
+        let mut text = String::new();
+        if self.days && duration_in_sec >= SECONDS_PER_DAY {
+            text = format!("{}d ", duration_in_sec / SECONDS_PER_DAY);
+            duration_in_sec %= SECONDS_PER_DAY;
+        }
         let seconds = duration_in_sec % 60;
         let minutes = (duration_in_sec % 3600) / 60;
         let hours = duration_in_sec / 3600;
 
-        format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+        text + &format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+    }
+}
+
+impl DurationFormatter for DecimalHoursFormatter {
+    fn format(&self, duration: Duration) -> String {
+        format!("{:.2}h", duration.as_secs_f64() / SECONDS_PER_HOUR as f64)
+    }
+}
+
+impl DurationFormatter for HumanFormatter {
+    fn format(&self, duration: Duration) -> String {
+        let unit = |count: u64, name: &str| match count {
+            1 => format!("1 {name}"),
+            _ => format!("{count} {name}s"),
+        };
+        let parts = Parts::of(duration);
+        let mut words = Vec::new();
+        if parts.hours > 0 {
+            words.push(unit(parts.hours, "hour"));
+        }
+        if parts.minutes > 0 {
+            words.push(unit(parts.minutes, "minute"));
+        }
+        if words.is_empty() {
+            words.push(unit(parts.seconds, "second"));
+        }
+        words.join(" ")
+    }
+}
+
+impl DurationFormatter for IsoFormatter {
+    fn format(&self, duration: Duration) -> String {
+        let parts = Parts::of(duration);
+        let mut text = "PT".to_string();
+        if parts.hours > 0 {
+            text += &format!("{}H", parts.hours);
+        }
+        if parts.minutes > 0 {
+            text += &format!("{}M", parts.minutes);
+        }
+        if parts.seconds > 0 || text.len() == 2 {
+            text += &format!("{}S", parts.seconds);
+        }
+        text
+    }
+}
+
+impl DurationFormatter for CompactFormatter {
+    fn format(&self, duration: Duration) -> String {
+        let parts = Parts::of(duration);
+        let mut text = String::new();
+        if parts.hours > 0 {
+            text += &format!("{}h", parts.hours);
+        }
+        if parts.minutes > 0 {
+            text += &format!("{}m", parts.minutes);
+        }
+        if text.is_empty() {
+            text = format!("{}s", parts.seconds);
+        }
+        text
+    }
+}
+
+/// A duration in whole hours, minutes and seconds
+struct Parts {
+    hours: u64,
+    minutes: u64,
+    seconds: u64,
+}
+
+impl Parts {
+    fn of(duration: Duration) -> Self {
+        let seconds = duration.as_secs();
+        Self {
+            hours: seconds / SECONDS_PER_HOUR,
+            minutes: seconds % SECONDS_PER_HOUR / SECONDS_PER_MINUTE,
+            seconds: seconds % SECONDS_PER_MINUTE,
+        }
     }
 }
 
@@ -45,4 +169,78 @@ mod tests {
 
         assert_eq!(&text, "03:11:00");
     }
+
+    #[test]
+    fn format_more_than_ninety_nine_hours() {
+        let duration = Duration::from_secs(123 * 3600 + 4 * 60 + 5);
+
+        let text = HourMinSecFormatter::default().format(duration);
+
+        assert_eq!(&text, "123:04:05");
+    }
+
+    #[test]
+    fn format_with_days() {
+        let formatter = HourMinSecFormatter::default().with_days();
+
+        assert_eq!(formatter.format(Duration::from_secs(11460)), "03:11:00");
+        assert_eq!(
+            formatter.format(Duration::from_secs(2 * 86400 + 11460)),
+            "2d 03:11:00"
+        );
+    }
+
+    #[test]
+    fn format_decimal_hours() {
+        let formatter = DecimalHoursFormatter;
+
+        assert_eq!(
+            formatter.format(Duration::from_secs(7 * 3600 + 900)),
+            "7.25h"
+        );
+        assert_eq!(formatter.format(Duration::from_secs(20 * 60)), "0.33h");
+        assert_eq!(formatter.format(Duration::ZERO), "0.00h");
+    }
+
+    #[test]
+    fn format_human() {
+        let formatter = HumanFormatter;
+
+        assert_eq!(
+            formatter.format(Duration::from_secs(7 * 3600 + 900 + 30)),
+            "7 hours 15 minutes"
+        );
+        assert_eq!(
+            formatter.format(Duration::from_secs(3660)),
+            "1 hour 1 minute"
+        );
+        assert_eq!(formatter.format(Duration::from_secs(45)), "45 seconds");
+    }
+
+    #[test]
+    fn format_iso() {
+        let formatter = IsoFormatter;
+
+        assert_eq!(
+            formatter.format(Duration::from_secs(7 * 3600 + 900)),
+            "PT7H15M"
+        );
+        assert_eq!(
+            formatter.format(Duration::from_secs(30 * 3600 + 5)),
+            "PT30H5S"
+        );
+        assert_eq!(formatter.format(Duration::ZERO), "PT0S");
+    }
+
+    #[test]
+    fn format_compact() {
+        let formatter = CompactFormatter;
+
+        assert_eq!(
+            formatter.format(Duration::from_secs(7 * 3600 + 900)),
+            "7h15m"
+        );
+        assert_eq!(formatter.format(Duration::from_secs(2 * 3600)), "2h");
+        assert_eq!(formatter.format(Duration::from_secs(45)), "45s");
+    }
 }
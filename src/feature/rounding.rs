@@ -0,0 +1,207 @@
+//! Rounding reported durations for billing
+//!
+//! Clients are billed in increments of a few minutes, and often for some
+//! minimum time per session. A [`Rounding`] works on the breakdown of a
+//! report and fills in the billable time of every group next to the time
+//! actually spent, so both can be shown.
+
+use std::time::Duration;
+
+use crate::feature::tracker::{Breakdown, TimeRecord};
+
+/// Which way durations are rounded to a whole increment
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// Halfway rounds up
+    #[default]
+    Nearest,
+    Up,
+    Down,
+}
+
+/// What the increment is applied to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scope {
+    /// Every record is rounded, groups add up the rounded records
+    #[default]
+    Record,
+    /// Groups add up the records, then the sum is rounded
+    Group,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Rounding {
+    /// No rounding if missing
+    increment: Option<Duration>,
+    direction: Direction,
+    scope: Scope,
+    /// Least time billed for a record
+    minimum: Duration,
+}
+
+impl Rounding {
+    /// Rounds to the nearest multiple of `increment`, record by record
+    pub fn new(increment: Duration) -> Self {
+        Self {
+            increment: Some(increment).filter(|increment| !increment.is_zero()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn with_scope(mut self, scope: Scope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Bills every record for at least `minimum`
+    ///
+    /// A record split between days, weeks or months counts as one record
+    /// for each of them.
+    pub fn with_minimum(mut self, minimum: Duration) -> Self {
+        self.minimum = minimum;
+        self
+    }
+
+    /// Rounds `duration` to a whole increment
+    pub fn round(&self, duration: Duration) -> Duration {
+        let Some(increment) = self.increment else {
+            return duration;
+        };
+        let (increment, length) = (increment.as_secs_f64(), duration.as_secs_f64());
+        let increments = match self.direction {
+            Direction::Nearest => (length / increment).round(),
+            Direction::Up => (length / increment).ceil(),
+            Direction::Down => (length / increment).floor(),
+        };
+        Duration::from_secs_f64(increments * increment)
+    }
+
+    /// Billable time of a single record
+    fn bill(&self, record: &TimeRecord) -> Duration {
+        match self.scope {
+            Scope::Record => self.round(record.duration()).max(self.minimum),
+            Scope::Group => record.duration().max(self.minimum),
+        }
+    }
+
+    /// Fills in the billable time of `breakdown` and all of its groups
+    pub fn apply(&self, breakdown: &mut Breakdown) {
+        let billed = breakdown
+            .records
+            .iter()
+            .map(|record| self.bill(record))
+            .sum();
+        breakdown.rounded = Some(match self.scope {
+            Scope::Record => billed,
+            Scope::Group => self.round(billed),
+        });
+        for group in breakdown.groups.values_mut() {
+            self.apply(group);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeDelta, TimeZone, Utc};
+
+    use crate::feature::tracker::{EndTime, GroupKey, SessionDetails, StartTime};
+
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn record(minutes: i64) -> TimeRecord {
+        let start = Utc.with_ymd_and_hms(2026, 10, 17, 9, 0, 0).unwrap();
+        TimeRecord::new(
+            StartTime::at(start),
+            EndTime::at(start + TimeDelta::minutes(minutes)),
+            SessionDetails::default(),
+        )
+    }
+
+    fn breakdown(minutes: &[i64]) -> Breakdown {
+        let records: Vec<_> = minutes.iter().copied().map(record).collect();
+        Breakdown {
+            total: records.iter().map(TimeRecord::duration).sum(),
+            records,
+            ..Breakdown::default()
+        }
+    }
+
+    #[test]
+    fn rounds_in_each_direction() {
+        let rounding = Rounding::new(15 * MINUTE);
+
+        assert_eq!(rounding.round(22 * MINUTE), 15 * MINUTE);
+        assert_eq!(
+            rounding.round(Duration::from_secs(22 * 60 + 30)),
+            30 * MINUTE
+        );
+        assert_eq!(
+            rounding
+                .clone()
+                .with_direction(Direction::Up)
+                .round(16 * MINUTE),
+            30 * MINUTE
+        );
+        assert_eq!(
+            rounding.with_direction(Direction::Down).round(29 * MINUTE),
+            15 * MINUTE
+        );
+    }
+
+    #[test]
+    fn records_are_rounded_before_adding_up() {
+        // Given two records of 20 minutes
+        let mut breakdown = breakdown(&[20, 20]);
+
+        // When they are rounded up to 30 minutes each
+        Rounding::new(30 * MINUTE)
+            .with_direction(Direction::Up)
+            .apply(&mut breakdown);
+
+        // Then an hour is billed
+        assert_eq!(breakdown.total, 40 * MINUTE);
+        assert_eq!(breakdown.rounded, Some(60 * MINUTE));
+    }
+
+    #[test]
+    fn groups_are_rounded_after_adding_up() {
+        // Given two records of 20 minutes in a group
+        let mut group = breakdown(&[20, 20]);
+        group.groups = [(GroupKey::Name(None), breakdown(&[20, 20]))].into();
+
+        // When their sum is rounded up to 30 minutes
+        Rounding::new(30 * MINUTE)
+            .with_direction(Direction::Up)
+            .with_scope(Scope::Group)
+            .apply(&mut group);
+
+        // Then an hour is billed for the group and its subgroup
+        assert_eq!(group.rounded, Some(60 * MINUTE));
+        assert_eq!(
+            group.groups[&GroupKey::Name(None)].rounded,
+            Some(60 * MINUTE)
+        );
+    }
+
+    #[test]
+    fn short_records_are_billed_the_minimum() {
+        // Given records of 5 and 50 minutes
+        let mut breakdown = breakdown(&[5, 50]);
+
+        // When at least 15 minutes are billed per record, in 6 minute steps
+        Rounding::new(6 * MINUTE)
+            .with_minimum(15 * MINUTE)
+            .apply(&mut breakdown);
+
+        // Then the short record counts for 15 minutes, the long one for 48
+        assert_eq!(breakdown.rounded, Some(63 * MINUTE));
+    }
+}
@@ -1,14 +1,32 @@
+mod atomic;
 mod flatfile;
+mod journal;
+mod lock;
+mod lockfile;
 mod reporter;
+mod sqlite;
+
+use std::{collections::BTreeSet, fmt, str::FromStr, time::Duration};
 
 use chrono::{DateTime, Utc};
-use error_stack::Result;
+use error_stack::{Result, ResultExt};
 use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::error::Suggestion;
 
 pub use flatfile::FlatFileTracker;
-pub use reporter::{ReportTimespan, Reporter, ReporterError};
+pub use journal::JournalTracker;
+pub use lockfile::LockfileData;
+pub use reporter::{
+    Breakdown, GroupBy, GroupKey, Report, ReportQuery, ReportTimespan, Reporter, ReporterError,
+};
+pub use sqlite::SqliteTracker;
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+/// How long to wait for another process to release the database by default
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub struct StartTime(DateTime<Utc>);
 
 impl StartTime {
@@ -16,6 +34,10 @@ impl StartTime {
         Self(Utc::now())
     }
 
+    pub const fn at(time: DateTime<Utc>) -> Self {
+        Self(time)
+    }
+
     pub const fn timestamp_millis(&self) -> i64 {
         self.0.timestamp_millis()
     }
@@ -28,15 +50,188 @@ impl EndTime {
         Self(Utc::now())
     }
 
+    pub const fn at(time: DateTime<Utc>) -> Self {
+        Self(time)
+    }
+
     pub const fn timestamp_millis(&self) -> i64 {
         self.0.timestamp_millis()
     }
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+/// What a session is spent on
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SessionDetails {
+    /// Client or project the time is billed to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Free-form labels, e.g. `review` or a ticket number
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    /// What was done, e.g. for standups and timesheets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl SessionDetails {
+    /// Adds to the description, keeping whatever was written before
+    pub fn append_description<D>(&mut self, text: D)
+    where
+        D: Into<String>,
+    {
+        let text = text.into();
+        self.description = match self.description.take() {
+            Some(description) => Some(format!("{description}; {text}")),
+            None => Some(text),
+        };
+    }
+}
+
+/// A pause within a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Break {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+impl Break {
+    pub fn duration(&self) -> Duration {
+        let millis = self.end.timestamp_millis() - self.start.timestamp_millis();
+        Duration::from_millis(millis.max(0) as u64)
+    }
+}
+
+/// Stable identifier of a record, ordered by the record's start time
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize,
+)]
+#[serde(transparent)]
+pub struct RecordId(Ulid);
+
+impl RecordId {
+    /// A fresh identifier for a record starting at `start`
+    fn generate(start: &StartTime) -> Self {
+        Self(Ulid::from_datetime(start.0.into()))
+    }
+
+    /// The identifier of a record stored before records had one
+    ///
+    /// It is derived from the start time and the position of the record in
+    /// storage, so reading the same data always gives the same identifier.
+    fn legacy(start_ms: i64, position: u64) -> Self {
+        Self(Ulid::from_parts(start_ms.max(0) as u64, position.into()))
+    }
+
+    /// Whether the identifier is missing from the stored record
+    fn is_nil(&self) -> bool {
+        self.0.is_nil()
+    }
+}
+
+impl fmt::Display for RecordId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for RecordId {
+    type Err = ulid::DecodeError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ulid::from_string(s).map(Self)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TimeRecord {
+    /// Missing in records stored before records had identifiers
+    #[serde(default, skip_serializing_if = "RecordId::is_nil")]
+    id: RecordId,
     start: StartTime,
     end: EndTime,
+    #[serde(flatten)]
+    details: SessionDetails,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    breaks: Vec<Break>,
+}
+
+impl TimeRecord {
+    /// A completed session without breaks, e.g. a meeting logged afterwards
+    pub fn new(start: StartTime, end: EndTime, details: SessionDetails) -> Self {
+        Self {
+            id: RecordId::generate(&start),
+            start,
+            end,
+            details,
+            breaks: Vec::new(),
+        }
+    }
+
+    pub fn id(&self) -> RecordId {
+        self.id
+    }
+
+    /// Whether this is a snapshot of the running session rather than a record
+    pub fn is_running(&self) -> bool {
+        self.id.is_nil()
+    }
+
+    /// Whether both records cover some of the same time
+    pub fn overlaps(&self, other: &TimeRecord) -> bool {
+        self.start.timestamp_millis() < other.end.timestamp_millis()
+            && other.start.timestamp_millis() < self.end.timestamp_millis()
+    }
+
+    /// The part of the record between `from` and `to`
+    ///
+    /// Breaks are cut to the same span, those outside of it are dropped.
+    pub fn clipped(mut self, from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        let start = self.start.0.max(from);
+        let end = self.end.0.min(to).max(start);
+        self.start = StartTime(start);
+        self.end = EndTime(end);
+        self.breaks = self
+            .breaks
+            .into_iter()
+            .map(|pause| Break {
+                start: pause.start.max(start),
+                end: pause.end.min(end),
+            })
+            .filter(|pause| pause.start < pause.end)
+            .collect();
+        self
+    }
+
+    /// Time spent working, i.e. excluding breaks
+    pub fn duration(&self) -> Duration {
+        let millis = self.end.timestamp_millis() - self.start.timestamp_millis();
+        let breaks = self.breaks.iter().map(Break::duration).sum();
+        Duration::from_millis(millis.max(0) as u64).saturating_sub(breaks)
+    }
+
+    pub fn breaks(&self) -> &[Break] {
+        &self.breaks
+    }
+
+    pub fn project(&self) -> Option<&str> {
+        self.details.project.as_deref()
+    }
+
+    pub fn tags(&self) -> &BTreeSet<String> {
+        &self.details.tags
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.details.description.as_deref()
+    }
+
+    pub fn start(&self) -> DateTime<Utc> {
+        self.start.0
+    }
+
+    pub fn end(&self) -> DateTime<Utc> {
+        self.end.0
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -47,51 +242,530 @@ pub enum StartupStatus {
     Running,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseStatus {
+    /// Time tracker paused
+    Paused,
+    /// Time tracker already paused
+    AlreadyPaused,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeStatus {
+    /// Time tracker resumed
+    Resumed,
+    /// Time tracker was not paused
+    NotPaused,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackerState {
+    /// No session is being tracked
+    Stopped,
+    /// A session is being tracked
+    Running,
+    /// A session is on a break
+    Paused,
+}
+
+/// What to do when an added record overlaps existing ones
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overlap {
+    /// Add the record anyway
+    #[default]
+    Allow,
+    /// Refuse to add the record
+    Refuse,
+}
+
 #[derive(Debug, thiserror::Error)]
 #[error("filesystem tracker error")]
 pub struct TrackerError;
 
+#[derive(Debug, thiserror::Error)]
+#[error("invalid record")]
+pub struct InvalidRecord;
+
+/// Changes to a record; fields left `None` are kept as they are
+#[derive(Debug, Clone, Default)]
+pub struct RecordEdit {
+    pub start: Option<StartTime>,
+    pub end: Option<EndTime>,
+    /// `Some(None)` removes the project
+    pub project: Option<Option<String>>,
+    pub tags: Option<BTreeSet<String>>,
+    /// `Some(None)` removes the description
+    pub description: Option<Option<String>>,
+}
+
+impl RecordEdit {
+    /// Applies the changes to `record`, refusing to make it end before it starts
+    ///
+    /// Breaks are cut to fit within the new start and end.
+    fn apply(self, mut record: TimeRecord) -> Result<TimeRecord, InvalidRecord> {
+        if let Some(start) = self.start {
+            record.start = start;
+        }
+        if let Some(end) = self.end {
+            record.end = end;
+        }
+        if let Some(project) = self.project {
+            record.details.project = project;
+        }
+        if let Some(tags) = self.tags {
+            record.details.tags = tags;
+        }
+        if let Some(description) = self.description {
+            record.details.description = description;
+        }
+
+        if record.end.timestamp_millis() < record.start.timestamp_millis() {
+            return Err(InvalidRecord)
+                .attach_printable(format!(
+                    "record {} would end at {} before it starts at {}",
+                    record.id, record.end.0, record.start.0
+                ))
+                .attach(Suggestion("pick an end time after the start time"));
+        }
+
+        let (start, end) = (record.start.0, record.end.0);
+        record.breaks = record
+            .breaks
+            .into_iter()
+            .filter_map(|pause| {
+                let pause = Break {
+                    start: pause.start.max(start),
+                    end: pause.end.min(end),
+                };
+                (pause.start < pause.end).then_some(pause)
+            })
+            .collect();
+        Ok(record)
+    }
+}
+
+/// Edits, deletions and additions applied together by [`Tracker::apply_changes`]
+#[derive(Debug, Default)]
+pub struct RecordChanges {
+    pub edits: Vec<(RecordId, RecordEdit)>,
+    pub deletions: Vec<RecordId>,
+    pub additions: Vec<TimeRecord>,
+}
+
+impl RecordChanges {
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty() && self.deletions.is_empty() && self.additions.is_empty()
+    }
+
+    /// Looks up the deleted and edited records among `records` and applies
+    /// the edits
+    ///
+    /// `records` must contain at least the records being deleted or edited.
+    fn resolve(self, mut records: Vec<TimeRecord>) -> Result<ResolvedChanges, InvalidRecord> {
+        let mut take = |id: RecordId| {
+            let index = records
+                .iter()
+                .position(|record| record.id == id && !record.is_running())
+                .ok_or_else(|| unknown_record(id))?;
+            Ok::<_, error_stack::Report<InvalidRecord>>(records.swap_remove(index))
+        };
+
+        let deleted = self
+            .deletions
+            .into_iter()
+            .map(&mut take)
+            .collect::<Result<Vec<_>, _>>()?;
+        let edited = self
+            .edits
+            .into_iter()
+            .map(|(id, edit)| {
+                let before = take(id)?;
+                let after = edit.apply(before.clone())?;
+                Ok((before, after))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for record in &self.additions {
+            check_times(record)?;
+        }
+
+        Ok(ResolvedChanges {
+            deleted,
+            edited,
+            added: self.additions,
+        })
+    }
+}
+
+/// [`RecordChanges`] with the records they affect looked up
+#[derive(Debug)]
+struct ResolvedChanges {
+    deleted: Vec<TimeRecord>,
+    /// Records before and after the edit
+    edited: Vec<(TimeRecord, TimeRecord)>,
+    added: Vec<TimeRecord>,
+}
+
+impl ResolvedChanges {
+    /// Records as they were before being deleted or edited
+    fn removed(&self) -> impl Iterator<Item = &TimeRecord> {
+        self.deleted
+            .iter()
+            .chain(self.edited.iter().map(|(before, _)| before))
+    }
+
+    /// Records as they are after being edited or added
+    fn changed(&self) -> impl Iterator<Item = &TimeRecord> {
+        self.edited
+            .iter()
+            .map(|(_, after)| after)
+            .chain(self.added.iter())
+    }
+
+    /// Refuses changes that leave an edited or added record overlapping
+    /// another one
+    ///
+    /// `existing` are the stored records, or at least those that may
+    /// overlap a changed one, and the running session up to now.
+    fn check_overlaps<I>(&self, existing: I) -> Result<(), InvalidRecord>
+    where
+        I: IntoIterator<Item = TimeRecord>,
+    {
+        let removed = self.removed().map(TimeRecord::id).collect::<Vec<_>>();
+        let kept = existing
+            .into_iter()
+            .filter(|record| record.is_running() || !removed.contains(&record.id))
+            .collect::<Vec<_>>();
+        let changed = self.changed().collect::<Vec<_>>();
+
+        for (index, record) in changed.iter().enumerate() {
+            let Some(other) = kept
+                .iter()
+                .chain(changed[index + 1..].iter().copied())
+                .find(|other| record.overlaps(other))
+            else {
+                continue;
+            };
+            let other = if other.is_running() {
+                "the running session".to_string()
+            } else {
+                format!("the record from {} to {}", other.start.0, other.end.0)
+            };
+            return Err(InvalidRecord)
+                .attach_printable(format!(
+                    "the record from {} to {} would overlap {other}",
+                    record.start.0, record.end.0
+                ))
+                .attach(Suggestion("change the times so that no records overlap"));
+        }
+
+        Ok(())
+    }
+}
+
+/// The error for an identifier no record has
+fn unknown_record(id: RecordId) -> error_stack::Report<InvalidRecord> {
+    error_stack::Report::new(InvalidRecord)
+        .attach_printable(format!("there is no record {id}"))
+        .attach(Suggestion(
+            "list records and their identifiers with `track log`",
+        ))
+}
+
+/// Checks a record about to be added against the `existing` ones, which
+/// include the running session up to now
+///
+/// Returns the existing records it overlaps.
+fn check_new_record<I>(
+    record: &TimeRecord,
+    existing: I,
+    overlap: Overlap,
+) -> Result<Vec<TimeRecord>, InvalidRecord>
+where
+    I: IntoIterator<Item = TimeRecord>,
+{
+    check_times(record)?;
+
+    let overlapping = existing
+        .into_iter()
+        .filter(|other| record.overlaps(other))
+        .collect::<Vec<_>>();
+    if overlap == Overlap::Refuse && !overlapping.is_empty() {
+        return Err(InvalidRecord)
+            .attach_printable(format!(
+                "record overlaps {} existing record(s)",
+                overlapping.len()
+            ))
+            .attach(Suggestion("leave out --no-overlap to add it anyway"));
+    }
+
+    Ok(overlapping)
+}
+
+/// Refuses a record ending before it starts
+fn check_times(record: &TimeRecord) -> Result<(), InvalidRecord> {
+    if record.end.timestamp_millis() < record.start.timestamp_millis() {
+        return Err(InvalidRecord)
+            .attach_printable(format!(
+                "record ends at {} before it starts at {}",
+                record.end.0, record.start.0
+            ))
+            .attach(Suggestion("pick an end time after the start time"));
+    }
+    Ok(())
+}
+
 pub trait Tracker {
-    fn start(&mut self) -> Result<StartupStatus, TrackerError>;
+    /// Starts tracking a session without any details
+    fn start(&mut self) -> Result<StartupStatus, TrackerError> {
+        self.start_with(SessionDetails::default())
+    }
+
+    /// Starts tracking a session spent on the given project and tags
+    fn start_with(&mut self, details: SessionDetails) -> Result<StartupStatus, TrackerError> {
+        self.start_at(StartTime::now(), details)
+    }
+
+    /// Starts tracking a session as if it had begun at `start`
+    fn start_at(
+        &mut self,
+        start: StartTime,
+        details: SessionDetails,
+    ) -> Result<StartupStatus, TrackerError>;
+
+    /// Stops tracking without adding to the description
+    fn stop(&mut self) -> Result<(), TrackerError> {
+        self.stop_with(None)
+    }
+
+    /// Stops tracking, appending `description` to the session's description
+    fn stop_with(&mut self, description: Option<String>) -> Result<(), TrackerError> {
+        self.stop_at(EndTime::now(), description)
+    }
 
-    fn stop(&mut self) -> Result<(), TrackerError>;
+    /// Stops tracking as if the session had ended at `end`
+    ///
+    /// Fails if `end` is before the start of the session.
+    fn stop_at(&mut self, end: EndTime, description: Option<String>) -> Result<(), TrackerError>;
 
-    fn is_running(&self) -> bool;
+    /// Replaces the description of the running session
+    fn set_description(&mut self, description: String) -> Result<(), TrackerError>;
+
+    /// Discards the running session without recording it
+    ///
+    /// Returns how long the discarded session was, or `None` if the tracker
+    /// was not running.
+    fn cancel(&mut self) -> Result<Option<Duration>, TrackerError>;
+
+    /// Adds a completed record, e.g. a meeting logged after the fact
+    ///
+    /// Records ending before they start are rejected. Returns the existing
+    /// records the new one overlaps, the running session included, unless
+    /// `overlap` refuses those.
+    fn add_record(
+        &mut self,
+        record: TimeRecord,
+        overlap: Overlap,
+    ) -> Result<Vec<TimeRecord>, TrackerError>;
+
+    /// Changes a record, returning it as it is stored now
+    fn edit_record(&mut self, id: RecordId, edit: RecordEdit) -> Result<TimeRecord, TrackerError>;
+
+    /// Removes a record, returning it
+    fn delete_record(&mut self, id: RecordId) -> Result<TimeRecord, TrackerError>;
+
+    /// Applies edits, deletions and additions together, e.g. those made in
+    /// an editor
+    ///
+    /// Nothing is changed if any of them fails, or if an edited or added
+    /// record would overlap another record or the running session.
+    fn apply_changes(&mut self, changes: RecordChanges) -> Result<(), TrackerError>;
+
+    /// Ends the running session, if any, and starts a new one at the same instant
+    fn switch(&mut self, details: SessionDetails) -> Result<(), TrackerError>;
+
+    /// Starts a break in the running session
+    fn pause(&mut self) -> Result<PauseStatus, TrackerError>;
+
+    /// Ends the break of a paused session
+    fn resume(&mut self) -> Result<ResumeStatus, TrackerError>;
+
+    fn state(&self) -> Result<TrackerState, TrackerError>;
+
+    /// The running session, `None` when stopped
+    fn current_session(&self) -> Result<Option<LockfileData>, TrackerError>;
+
+    /// Whether a session is being tracked and not paused
+    fn is_running(&self) -> bool {
+        matches!(self.state(), Ok(TrackerState::Running))
+    }
 
     fn records(&self) -> Result<impl Iterator<Item = TimeRecord>, TrackerError>;
+
+    /// Records started at or after `since`
+    ///
+    /// Backends able to query by start time should override this so that
+    /// reports don't need to load every record.
+    fn records_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<impl Iterator<Item = TimeRecord>, TrackerError> {
+        let since = since.timestamp_millis();
+        Ok(self
+            .records()?
+            .filter(move |rec| rec.start.timestamp_millis() >= since))
+    }
+
+    /// Records covering some time between `from` and `to`
+    ///
+    /// Unlike [`Tracker::records_since`] this includes records that started
+    /// before `from` but were still running at that time.
+    fn records_overlapping(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<impl Iterator<Item = TimeRecord>, TrackerError> {
+        let (from, to) = (from.timestamp_millis(), to.timestamp_millis());
+        Ok(self.records()?.filter(move |rec| {
+            rec.start.timestamp_millis() < to && rec.end.timestamp_millis() > from
+        }))
+    }
 }
 
 #[cfg(test)]
 mod tlib {
     use super::*;
+    use crate::feature::tracker::lockfile::LockfileData;
 
     #[derive(Debug, Default)]
     pub struct FakeTracker {
-        tracking: Option<StartTime>,
+        tracking: Option<LockfileData>,
         records: Vec<TimeRecord>,
     }
 
     impl Tracker for FakeTracker {
-        fn start(&mut self) -> Result<StartupStatus, TrackerError> {
+        fn start_at(
+            &mut self,
+            start: StartTime,
+            details: SessionDetails,
+        ) -> Result<StartupStatus, TrackerError> {
             if self.tracking.is_some() {
                 Ok(StartupStatus::Running)
             } else {
-                self.tracking = Some(StartTime::now());
+                self.tracking = Some(LockfileData::new(start, details));
                 Ok(StartupStatus::Started)
             }
         }
 
-        fn stop(&mut self) -> Result<(), TrackerError> {
-            let start = self.tracking.take().unwrap();
-            let end = EndTime::now();
-            let record = TimeRecord { start, end };
+        fn stop_at(
+            &mut self,
+            end: EndTime,
+            description: Option<String>,
+        ) -> Result<(), TrackerError> {
+            let mut session = self.tracking.take().unwrap();
+            if let Some(description) = description {
+                session.details.append_description(description);
+            }
+            self.records
+                .push(session.finish_at(end).change_context(TrackerError)?);
+
+            Ok(())
+        }
+
+        fn set_description(&mut self, description: String) -> Result<(), TrackerError> {
+            let session = self.tracking.as_mut().ok_or(TrackerError)?;
+            session.details.description = Some(description);
+            Ok(())
+        }
+
+        fn cancel(&mut self) -> Result<Option<Duration>, TrackerError> {
+            Ok(self
+                .tracking
+                .take()
+                .map(|session| session.finish(EndTime::now()).duration()))
+        }
+
+        fn add_record(
+            &mut self,
+            record: TimeRecord,
+            overlap: Overlap,
+        ) -> Result<Vec<TimeRecord>, TrackerError> {
+            let overlapping = check_new_record(&record, self.records.clone(), overlap)
+                .change_context(TrackerError)?;
             self.records.push(record);
+            Ok(overlapping)
+        }
+
+        fn edit_record(
+            &mut self,
+            id: RecordId,
+            edit: RecordEdit,
+        ) -> Result<TimeRecord, TrackerError> {
+            let record = self
+                .records
+                .iter_mut()
+                .find(|record| record.id == id)
+                .ok_or_else(|| unknown_record(id))
+                .change_context(TrackerError)?;
+            *record = edit.apply(record.clone()).change_context(TrackerError)?;
+            Ok(record.clone())
+        }
+
+        fn delete_record(&mut self, id: RecordId) -> Result<TimeRecord, TrackerError> {
+            let index = self
+                .records
+                .iter()
+                .position(|record| record.id == id)
+                .ok_or_else(|| unknown_record(id))
+                .change_context(TrackerError)?;
+            Ok(self.records.remove(index))
+        }
+
+        fn apply_changes(&mut self, changes: RecordChanges) -> Result<(), TrackerError> {
+            let changes = changes
+                .resolve(self.records.clone())
+                .change_context(TrackerError)?;
+            let running = self
+                .tracking
+                .as_ref()
+                .map(|session| session.snapshot(Utc::now()));
+            changes
+                .check_overlaps(self.records.iter().cloned().chain(running))
+                .change_context(TrackerError)?;
 
+            let removed = changes.removed().map(TimeRecord::id).collect::<Vec<_>>();
+            self.records.retain(|record| !removed.contains(&record.id));
+            self.records.extend(changes.changed().cloned());
             Ok(())
         }
 
-        fn is_running(&self) -> bool {
-            self.tracking.is_some()
+        fn switch(&mut self, details: SessionDetails) -> Result<(), TrackerError> {
+            let now = Utc::now();
+            if let Some(session) = self.tracking.take() {
+                self.records.push(session.finish(EndTime(now)));
+            }
+            self.tracking = Some(LockfileData::new(StartTime(now), details));
+            Ok(())
+        }
+
+        fn pause(&mut self) -> Result<PauseStatus, TrackerError> {
+            let session = self.tracking.as_mut().ok_or(TrackerError)?;
+            Ok(session.pause(Utc::now()))
+        }
+
+        fn resume(&mut self) -> Result<ResumeStatus, TrackerError> {
+            let session = self.tracking.as_mut().ok_or(TrackerError)?;
+            Ok(session.resume(Utc::now()))
+        }
+
+        fn state(&self) -> Result<TrackerState, TrackerError> {
+            Ok(self
+                .tracking
+                .as_ref()
+                .map_or(TrackerState::Stopped, LockfileData::state))
+        }
+
+        fn current_session(&self) -> Result<Option<LockfileData>, TrackerError> {
+            Ok(self.tracking.clone())
         }
 
         fn records(&self) -> Result<impl Iterator<Item = TimeRecord>, TrackerError> {
@@ -0,0 +1,97 @@
+//! Crash-safe file replacement
+
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use error_stack::{Result, ResultExt};
+
+#[derive(Debug, thiserror::Error)]
+#[error("atomic write error")]
+pub struct AtomicWriteError;
+
+/// Replaces the contents of `path` so that readers only ever observe the old
+/// or the new version, even if the process crashes midway
+///
+/// The contents are written to a temporary sibling file, synced to disk and
+/// then renamed over `path`.
+pub fn replace<P>(path: P, contents: &[u8]) -> Result<(), AtomicWriteError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let tmp_path = sibling_path(path, "tmp");
+    let mut tmp = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&tmp_path)
+        .change_context(AtomicWriteError)
+        .attach_printable_lazy(|| format!("failed to open '{}'", tmp_path.display()))?;
+    tmp.write_all(contents)
+        .change_context(AtomicWriteError)
+        .attach_printable_lazy(|| format!("failed to write '{}'", tmp_path.display()))?;
+    tmp.sync_all()
+        .change_context(AtomicWriteError)
+        .attach_printable_lazy(|| format!("failed to sync '{}'", tmp_path.display()))?;
+    drop(tmp);
+
+    std::fs::rename(&tmp_path, path)
+        .change_context(AtomicWriteError)
+        .attach_printable_lazy(|| format!("failed to replace '{}'", path.display()))?;
+    sync_parent_dir(path)
+}
+
+/// Copies `path` to its backup location, if it exists
+///
+/// The backup is replaced like any other file, so a crash leaves the previous
+/// backup in place.
+pub fn backup<P>(path: P) -> Result<(), AtomicWriteError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let contents = match std::fs::read(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => {
+            return Err(e)
+                .change_context(AtomicWriteError)
+                .attach_printable_lazy(|| format!("failed to read '{}'", path.display()))
+        }
+    };
+    replace(backup_path(path), &contents)
+        .attach_printable_lazy(|| format!("failed to back up '{}'", path.display()))
+}
+
+/// Location of the previous version of a file
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling_path(path, "bak")
+}
+
+/// Appends `extension` to the file name of `path`, e.g. `records.json.bak`
+pub fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(extension);
+    path.with_file_name(name)
+}
+
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> Result<(), AtomicWriteError> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    std::fs::File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .change_context(AtomicWriteError)
+        .attach_printable_lazy(|| format!("failed to sync directory '{}'", dir.display()))
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> Result<(), AtomicWriteError> {
+    Ok(())
+}
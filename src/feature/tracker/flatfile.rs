@@ -3,18 +3,35 @@
 // flat file tracker
 // - "lockfile": tracker is running
 // - "database file": JSON doc
+// - "history file": changes that can be undone
+
+mod history;
+mod migration;
 
 use std::{
     fs::OpenOptions,
-    io::{Read, Write},
+    io::Read,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
+use chrono::Utc;
 use error_stack::{Result, ResultExt};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::feature::tracker::{
-    EndTime, Reporter, StartTime, StartupStatus, TimeRecord, Tracker, TrackerError,
+use crate::{
+    error::Suggestion,
+    feature::tracker::{
+        atomic::{self, backup_path, sibling_path},
+        check_new_record,
+        flatfile::history::{Change, History, SessionChange},
+        lock::{FileLock, LockMode},
+        lockfile::{self, LockfileData},
+        unknown_record, EndTime, InvalidRecord, Overlap, PauseStatus, RecordChanges, RecordEdit,
+        RecordId, Reporter, ResumeStatus, SessionDetails, StartTime, StartupStatus, TimeRecord,
+        Tracker, TrackerError, TrackerState, DEFAULT_LOCK_TIMEOUT,
+    },
 };
 
 #[derive(Debug, thiserror::Error)]
@@ -22,24 +39,47 @@ use crate::feature::tracker::{
 pub struct FlatFileTrackerError;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
-struct LockfileData {
-    start_time: StartTime,
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 struct FlatFileDatabase {
+    version: u32,
     records: Vec<TimeRecord>,
 }
 
+impl Default for FlatFileDatabase {
+    fn default() -> Self {
+        Self {
+            version: migration::CURRENT_VERSION,
+            records: Vec::default(),
+        }
+    }
+}
+
 impl FlatFileDatabase {
     fn push(&mut self, record: TimeRecord) {
         self.records.push(record);
     }
+
+    /// Inserts a record after all records starting no later than it
+    fn insert(&mut self, record: TimeRecord) {
+        let index = self.records.partition_point(|other| {
+            other.start.timestamp_millis() <= record.start.timestamp_millis()
+        });
+        self.records.insert(index, record);
+    }
+
+    fn remove(&mut self, id: RecordId) -> Result<TimeRecord, InvalidRecord> {
+        let index = self
+            .records
+            .iter()
+            .position(|record| record.id == id)
+            .ok_or_else(|| unknown_record(id))?;
+        Ok(self.records.remove(index))
+    }
 }
 
 pub struct FlatFileTracker {
     db: PathBuf,
     lockfile: PathBuf,
+    lock_timeout: Duration,
 }
 
 impl FlatFileTracker {
@@ -50,63 +90,440 @@ impl FlatFileTracker {
     {
         let db = db.into();
         let lockfile = lockfile.into();
-        Self { db, lockfile }
+        Self {
+            db,
+            lockfile,
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+        }
     }
 
-    fn start_impl(&self) -> Result<StartupStatus, FlatFileTrackerError> {
-        let lockfile_data = {
-            let start_time = StartTime::now();
-            let data = LockfileData { start_time };
-            serde_json::to_string(&data)
-                .change_context(FlatFileTrackerError)
-                .attach_printable("failed to serialize lockfile data")?
-        };
+    /// Sets how long to wait for other processes to release the database
+    pub fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = timeout;
+        self
+    }
 
-        let file = OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .open(&self.lockfile);
+    /// Reverts the most recent command, returning its name
+    pub fn undo(&mut self) -> Result<Option<String>, TrackerError> {
+        self.undo_impl().change_context(TrackerError)
+    }
 
-        if let Ok(mut file) = file {
-            file.write_all(lockfile_data.as_bytes())
-                .change_context(FlatFileTrackerError)
-                .attach_printable("failed to write lockfile data")?;
-            Ok(StartupStatus::Started)
-        } else {
-            Ok(StartupStatus::Running)
+    /// Applies the most recently undone command again, returning its name
+    pub fn redo(&mut self) -> Result<Option<String>, TrackerError> {
+        self.redo_impl().change_context(TrackerError)
+    }
+
+    fn history_path(&self) -> PathBuf {
+        sibling_path(&self.db, "history")
+    }
+
+    /// Acquires the advisory lock guarding the database and lockfile
+    fn lock(&self, mode: LockMode) -> Result<FileLock, FlatFileTrackerError> {
+        FileLock::acquire(sibling_path(&self.db, "lock"), mode, self.lock_timeout)
+            .change_context(FlatFileTrackerError)
+            .attach_printable("failed to lock database")
+            .attach(Suggestion(
+                "another `track` process is using the database; \
+                 try again or raise the lock timeout with --lock-timeout",
+            ))
+    }
+
+    fn start_impl(
+        &self,
+        start: StartTime,
+        details: SessionDetails,
+    ) -> Result<StartupStatus, FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let session = LockfileData::new(start, details);
+        let status =
+            lockfile::create(&self.lockfile, &session).change_context(FlatFileTrackerError)?;
+        if status == StartupStatus::Started {
+            self.remember(Change::new("start").with_session(None, Some(session)));
         }
+
+        Ok(status)
     }
 
-    fn stop_impl(&self) -> Result<(), FlatFileTrackerError> {
-        let start = read_lockfile(&self.lockfile)?.start_time;
-        let end = EndTime::now();
-        let record = TimeRecord { start, end };
-        let mut db = load_database(&self.db)?;
-        db.push(record);
+    fn stop_impl(
+        &self,
+        end: EndTime,
+        description: Option<String>,
+    ) -> Result<(), FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let mut session = lockfile::read(&self.lockfile).change_context(FlatFileTrackerError)?;
+        let before = session.clone();
+        if let Some(description) = description {
+            session.details.append_description(description);
+        }
+        let record = session
+            .finish_at(end)
+            .change_context(FlatFileTrackerError)?;
+        let mut db = load_database(&self.db, LockMode::Exclusive)?;
+        db.push(record.clone());
         save_database(&self.db, &db)?;
 
-        std::fs::remove_file(&self.lockfile)
-            .change_context(FlatFileTrackerError)
-            .attach_printable("unable to delete lockfile")?;
+        lockfile::remove(&self.lockfile).change_context(FlatFileTrackerError)?;
+        self.remember(
+            Change::new("stop")
+                .with_records(Vec::default(), vec![record])
+                .with_session(Some(before), None),
+        );
+
+        Ok(())
+    }
+
+    fn set_description_impl(&self, description: String) -> Result<(), FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let (before, after) = lockfile::update(&self.lockfile, |session| {
+            let before = session.clone();
+            session.details.description = Some(description);
+            (before, session.clone())
+        })
+        .change_context(FlatFileTrackerError)?;
+        self.remember(Change::new("note").with_session(Some(before), Some(after)));
+
         Ok(())
     }
+
+    fn cancel_impl(&self) -> Result<Option<Duration>, FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        if !self.lockfile.exists() {
+            return Ok(None);
+        }
+        let session = lockfile::read(&self.lockfile).change_context(FlatFileTrackerError)?;
+        lockfile::remove(&self.lockfile).change_context(FlatFileTrackerError)?;
+
+        let duration = session.clone().finish(EndTime::now()).duration();
+        self.remember(Change::new("cancel").with_session(Some(session), None));
+        Ok(Some(duration))
+    }
+
+    fn add_record_impl(
+        &self,
+        record: TimeRecord,
+        overlap: Overlap,
+    ) -> Result<Vec<TimeRecord>, FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let mut db = load_database(&self.db, LockMode::Exclusive)?;
+        let running = lockfile::session(&self.lockfile)
+            .change_context(FlatFileTrackerError)?
+            .map(|session| session.snapshot(Utc::now()));
+        let existing = db.records.iter().cloned().chain(running);
+        let overlapping =
+            check_new_record(&record, existing, overlap).change_context(FlatFileTrackerError)?;
+        db.insert(record.clone());
+        save_database(&self.db, &db)?;
+        self.remember(Change::new("add").with_records(Vec::default(), vec![record]));
+
+        Ok(overlapping)
+    }
+
+    fn edit_record_impl(
+        &self,
+        id: RecordId,
+        edit: RecordEdit,
+    ) -> Result<TimeRecord, FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let mut db = load_database(&self.db, LockMode::Exclusive)?;
+        let before = db.remove(id).change_context(FlatFileTrackerError)?;
+        let record = edit
+            .apply(before.clone())
+            .change_context(FlatFileTrackerError)?;
+        db.insert(record.clone());
+        save_database(&self.db, &db)?;
+        self.remember(Change::new("edit").with_records(vec![before], vec![record.clone()]));
+
+        Ok(record)
+    }
+
+    fn delete_record_impl(&self, id: RecordId) -> Result<TimeRecord, FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let mut db = load_database(&self.db, LockMode::Exclusive)?;
+        let record = db.remove(id).change_context(FlatFileTrackerError)?;
+        save_database(&self.db, &db)?;
+        self.remember(Change::new("delete").with_records(vec![record.clone()], Vec::default()));
+
+        Ok(record)
+    }
+
+    fn apply_changes_impl(&self, changes: RecordChanges) -> Result<(), FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let mut db = load_database(&self.db, LockMode::Exclusive)?;
+        let changes = changes
+            .resolve(db.records.clone())
+            .change_context(FlatFileTrackerError)?;
+        let running = lockfile::session(&self.lockfile)
+            .change_context(FlatFileTrackerError)?
+            .map(|session| session.snapshot(Utc::now()));
+        changes
+            .check_overlaps(db.records.iter().cloned().chain(running))
+            .change_context(FlatFileTrackerError)?;
+
+        let removed = changes.removed().cloned().collect::<Vec<_>>();
+        let added = changes.changed().cloned().collect::<Vec<_>>();
+        for record in &removed {
+            db.remove(record.id).change_context(FlatFileTrackerError)?;
+        }
+        for record in &added {
+            db.insert(record.clone());
+        }
+        save_database(&self.db, &db)?;
+        self.remember(Change::new("edit").with_records(removed, added));
+
+        Ok(())
+    }
+
+    fn switch_impl(&self, details: SessionDetails) -> Result<(), FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let now = Utc::now();
+        let mut before = None;
+        let mut added = Vec::default();
+        if self.lockfile.exists() {
+            let session = lockfile::read(&self.lockfile).change_context(FlatFileTrackerError)?;
+            let record = session.clone().finish(EndTime(now));
+            let mut db = load_database(&self.db, LockMode::Exclusive)?;
+            db.push(record.clone());
+            save_database(&self.db, &db)?;
+            before = Some(session);
+            added.push(record);
+        }
+
+        let session = LockfileData::new(StartTime(now), details);
+        lockfile::write(&self.lockfile, &session).change_context(FlatFileTrackerError)?;
+        self.remember(
+            Change::new("switch")
+                .with_records(Vec::default(), added)
+                .with_session(before, Some(session)),
+        );
+
+        Ok(())
+    }
+
+    fn pause_impl(&self) -> Result<PauseStatus, FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let (status, before, after) = lockfile::update(&self.lockfile, |session| {
+            let before = session.clone();
+            (session.pause(Utc::now()), before, session.clone())
+        })
+        .change_context(FlatFileTrackerError)?;
+        if status == PauseStatus::Paused {
+            self.remember(Change::new("pause").with_session(Some(before), Some(after)));
+        }
+
+        Ok(status)
+    }
+
+    fn resume_impl(&self) -> Result<ResumeStatus, FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let (status, before, after) = lockfile::update(&self.lockfile, |session| {
+            let before = session.clone();
+            (session.resume(Utc::now()), before, session.clone())
+        })
+        .change_context(FlatFileTrackerError)?;
+        if status == ResumeStatus::Resumed {
+            self.remember(Change::new("resume").with_session(Some(before), Some(after)));
+        }
+
+        Ok(status)
+    }
+
+    fn state_impl(&self) -> Result<TrackerState, FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Shared)?;
+
+        lockfile::state(&self.lockfile).change_context(FlatFileTrackerError)
+    }
+
+    fn current_session_impl(&self) -> Result<Option<LockfileData>, FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Shared)?;
+
+        lockfile::session(&self.lockfile).change_context(FlatFileTrackerError)
+    }
+
+    fn records_impl(&self) -> Result<FlatFileDatabase, FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Shared)?;
+
+        load_database(&self.db, LockMode::Shared)
+    }
+
+    fn undo_impl(&self) -> Result<Option<String>, FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let path = self.history_path();
+        let mut history = history::load(&path).change_context(FlatFileTrackerError)?;
+        let Some(change) = history.pop_undo() else {
+            return Ok(None);
+        };
+        self.apply_change(change.clone().inverse())?;
+
+        let command = change.command.clone();
+        history.push_redo(change);
+        history::save(&path, &history).change_context(FlatFileTrackerError)?;
+        Ok(Some(command))
+    }
+
+    fn redo_impl(&self) -> Result<Option<String>, FlatFileTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let path = self.history_path();
+        let mut history = history::load(&path).change_context(FlatFileTrackerError)?;
+        let Some(change) = history.pop_redo() else {
+            return Ok(None);
+        };
+        self.apply_change(change.clone())?;
+
+        let command = change.command.clone();
+        history.push_undo(change);
+        history::save(&path, &history).change_context(FlatFileTrackerError)?;
+        Ok(Some(command))
+    }
+
+    /// Removes and adds the records of `change` and sets its session
+    ///
+    /// Nothing is touched if the database no longer matches the state the
+    /// change was made from, e.g. because the files were edited by hand.
+    fn apply_change(&self, change: Change) -> Result<(), FlatFileTrackerError> {
+        let diverged = || {
+            Suggestion(
+                "the database was changed outside of `track`; \
+                 fix it by hand or remove the '.history' file next to it",
+            )
+        };
+        if let Some(session) = &change.session {
+            let running = lockfile::session(&self.lockfile).change_context(FlatFileTrackerError)?;
+            if running != session.before {
+                return Err(FlatFileTrackerError)
+                    .attach_printable(format!(
+                        "the running session no longer matches `{}`",
+                        change.command
+                    ))
+                    .attach(Suggestion(
+                        "another session was started or changed outside of the undo history; \
+                         stop or cancel it first, or remove the '.history' file next to the database",
+                    ));
+            }
+        }
+
+        if !change.removed.is_empty() || !change.added.is_empty() {
+            let mut db = load_database(&self.db, LockMode::Exclusive)?;
+            for record in &change.removed {
+                db.remove(record.id)
+                    .change_context(FlatFileTrackerError)
+                    .attach_printable_lazy(|| {
+                        format!("the records no longer match `{}`", change.command)
+                    })
+                    .attach(diverged())?;
+            }
+            for record in change.added {
+                db.insert(record);
+            }
+            save_database(&self.db, &db)?;
+        }
+
+        match change.session {
+            Some(SessionChange {
+                after: Some(session),
+                ..
+            }) => lockfile::write(&self.lockfile, &session),
+            Some(SessionChange { after: None, .. }) => lockfile::remove(&self.lockfile),
+            None => Ok(()),
+        }
+        .change_context(FlatFileTrackerError)
+    }
+
+    /// Records a command in the undo history
+    ///
+    /// The command already took effect, so a broken history is only logged.
+    fn remember(&self, change: Change) {
+        let path = self.history_path();
+        let mut history = history::load(&path).unwrap_or_else(|e| {
+            tracing::warn!(history = %path.display(), "starting a new undo history: {e:?}");
+            History::default()
+        });
+        history.record(change);
+        if let Err(e) = history::save(&path, &history) {
+            tracing::warn!(history = %path.display(), "failed to save undo history: {e:?}");
+        }
+    }
 }
 
 impl Tracker for FlatFileTracker {
-    fn start(&mut self) -> Result<StartupStatus, TrackerError> {
-        self.start_impl().change_context(TrackerError)
+    fn start_at(
+        &mut self,
+        start: StartTime,
+        details: SessionDetails,
+    ) -> Result<StartupStatus, TrackerError> {
+        self.start_impl(start, details).change_context(TrackerError)
+    }
+
+    fn stop_at(&mut self, end: EndTime, description: Option<String>) -> Result<(), TrackerError> {
+        self.stop_impl(end, description)
+            .change_context(TrackerError)
+    }
+
+    fn set_description(&mut self, description: String) -> Result<(), TrackerError> {
+        self.set_description_impl(description)
+            .change_context(TrackerError)
+    }
+
+    fn cancel(&mut self) -> Result<Option<Duration>, TrackerError> {
+        self.cancel_impl().change_context(TrackerError)
+    }
+
+    fn add_record(
+        &mut self,
+        record: TimeRecord,
+        overlap: Overlap,
+    ) -> Result<Vec<TimeRecord>, TrackerError> {
+        self.add_record_impl(record, overlap)
+            .change_context(TrackerError)
+    }
+
+    fn edit_record(&mut self, id: RecordId, edit: RecordEdit) -> Result<TimeRecord, TrackerError> {
+        self.edit_record_impl(id, edit).change_context(TrackerError)
+    }
+
+    fn delete_record(&mut self, id: RecordId) -> Result<TimeRecord, TrackerError> {
+        self.delete_record_impl(id).change_context(TrackerError)
+    }
+
+    fn apply_changes(&mut self, changes: RecordChanges) -> Result<(), TrackerError> {
+        self.apply_changes_impl(changes)
+            .change_context(TrackerError)
+    }
+
+    fn switch(&mut self, details: SessionDetails) -> Result<(), TrackerError> {
+        self.switch_impl(details).change_context(TrackerError)
+    }
+
+    fn pause(&mut self) -> Result<PauseStatus, TrackerError> {
+        self.pause_impl().change_context(TrackerError)
+    }
+
+    fn resume(&mut self) -> Result<ResumeStatus, TrackerError> {
+        self.resume_impl().change_context(TrackerError)
     }
 
-    fn stop(&mut self) -> Result<(), TrackerError> {
-        self.stop_impl().change_context(TrackerError)
+    fn state(&self) -> Result<TrackerState, TrackerError> {
+        self.state_impl().change_context(TrackerError)
     }
 
-    fn is_running(&self) -> bool {
-        self.lockfile.exists()
+    fn current_session(&self) -> Result<Option<LockfileData>, TrackerError> {
+        self.current_session_impl().change_context(TrackerError)
     }
 
     fn records(&self) -> Result<impl Iterator<Item = TimeRecord>, TrackerError> {
-        let db = load_database(&self.db).change_context(TrackerError)?;
+        let db = self.records_impl().change_context(TrackerError)?;
         Ok(db.records.into_iter())
     }
 }
@@ -117,26 +534,97 @@ fn save_database<P>(path: P, db: &FlatFileDatabase) -> Result<(), FlatFileTracke
 where
     P: AsRef<Path>,
 {
+    let path = path.as_ref();
     let db = serde_json::to_string(&db)
         .change_context(FlatFileTrackerError)
         .attach_printable("failed to serialize database")?;
-    OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(false)
-        .open(path.as_ref())
+
+    // a corrupted database was loaded from the backup, which must survive
+    if is_intact(path) {
+        atomic::backup(path)
+            .change_context(FlatFileTrackerError)
+            .attach_printable("failed to back up database")?;
+    }
+    atomic::replace(path, db.as_bytes())
         .change_context(FlatFileTrackerError)
-        .attach_printable("failed to open database")?
-        .write_all(db.as_bytes())
+        .attach_printable("failed to write database")
+}
+
+/// Whether the database at `path` loads, and so is worth keeping as backup
+fn is_intact(path: &Path) -> bool {
+    std::fs::read_to_string(path)
         .change_context(FlatFileTrackerError)
-        .attach_printable("failed to write database")?;
-    Ok(())
+        .and_then(|buf| parse_database(&buf))
+        .and_then(|doc| {
+            let version = migration::version_of(&doc).change_context(FlatFileTrackerError)?;
+            ensure_supported(version)?;
+            decode_database(doc, version)
+        })
+        .is_ok()
 }
 
-fn load_database<P>(db: P) -> Result<FlatFileDatabase, FlatFileTrackerError>
+/// Loads the database, falling back to its backup if it is corrupted
+///
+/// `lock` is the lock held on the database. Older databases are only backed
+/// up before migrating under an exclusive lock, as only then can the
+/// migrated database be saved over them.
+fn load_database<P>(db: P, lock: LockMode) -> Result<FlatFileDatabase, FlatFileTrackerError>
 where
     P: AsRef<Path>,
 {
+    let db = db.as_ref();
+    let db_buf = read_database_file(db)?;
+
+    let loaded = match parse_database(&db_buf) {
+        Ok(doc) => {
+            // a newer database is intact, the backup would lose its changes
+            if let Ok(version) = migration::version_of(&doc) {
+                ensure_supported(version)?;
+            }
+            upgrade_database(db, doc, lock)
+        }
+        Err(e) => Err(e),
+    };
+    let e = match loaded {
+        Ok(database) => return Ok(database),
+        Err(e) => e,
+    };
+
+    let backup = backup_path(db);
+    let restored = if backup.exists() {
+        read_database_file(&backup)
+            .and_then(|buf| parse_database(&buf))
+            .and_then(|doc| upgrade_database(&backup, doc, lock))
+            .ok()
+    } else {
+        None
+    };
+    match restored {
+        Some(database) => {
+            tracing::warn!(
+                db = %db.display(),
+                "database is corrupted, falling back to backup"
+            );
+            eprintln!(
+                "warning: the database '{}' is corrupted, using its backup '{}'",
+                db.display(),
+                backup.display()
+            );
+            eprintln!(
+                "suggestion: copy '{}' elsewhere to inspect it; \
+                 the next change overwrites it",
+                db.display()
+            );
+            Ok(database)
+        }
+        None => Err(e.attach(Suggestion(
+            "the database is corrupted and no usable backup exists; \
+             move it aside or restore it from the '.bak' file by hand",
+        ))),
+    }
+}
+
+fn read_database_file(db: &Path) -> Result<String, FlatFileTrackerError> {
     let mut db_buf = String::default();
 
     OpenOptions::new()
@@ -144,40 +632,82 @@ where
         .write(true)
         .create(true)
         .truncate(false)
-        .open(db.as_ref())
+        .open(db)
         .change_context(FlatFileTrackerError)
         .attach_printable("failed to load database")?
         .read_to_string(&mut db_buf)
         .change_context(FlatFileTrackerError)
         .attach_printable("failed to read database")?;
 
+    Ok(db_buf)
+}
+
+fn parse_database(db_buf: &str) -> Result<Value, FlatFileTrackerError> {
     if db_buf.is_empty() {
-        Ok(FlatFileDatabase::default())
+        serde_json::to_value(FlatFileDatabase::default())
     } else {
-        Ok(serde_json::from_str(&db_buf)
-            .change_context(FlatFileTrackerError)
-            .attach_printable("failed to deserialize database")?)
+        serde_json::from_str(db_buf)
     }
+    .change_context(FlatFileTrackerError)
+    .attach_printable("failed to deserialize database")
 }
 
-fn read_lockfile<P>(lockfile: P) -> Result<LockfileData, FlatFileTrackerError>
-where
-    P: AsRef<Path>,
-{
-    let file = OpenOptions::new()
-        .read(true)
-        .open(lockfile.as_ref())
-        .change_context(FlatFileTrackerError)
-        .attach_printable("failed to open lockfile")?;
+/// Brings a database document read from `source` up to the current version
+///
+/// Under an exclusive `lock`, the original file is copied next to itself
+/// before an older document is migrated, so that it can be restored should
+/// the migration go wrong.
+fn upgrade_database(
+    source: &Path,
+    doc: Value,
+    lock: LockMode,
+) -> Result<FlatFileDatabase, FlatFileTrackerError> {
+    let version = migration::version_of(&doc).change_context(FlatFileTrackerError)?;
+    ensure_supported(version)?;
 
-    serde_json::from_reader(file)
+    if version < migration::CURRENT_VERSION {
+        let backup = sibling_path(source, &format!("v{version}.bak"));
+        if lock == LockMode::Exclusive && source.exists() && !backup.exists() {
+            std::fs::copy(source, &backup)
+                .change_context(FlatFileTrackerError)
+                .attach_printable("failed to back up database before migrating")?;
+        }
+    }
+    decode_database(doc, version)
+}
+
+/// Migrates `doc` from `version` and reads the database out of it
+fn decode_database(doc: Value, version: u32) -> Result<FlatFileDatabase, FlatFileTrackerError> {
+    let doc = if version < migration::CURRENT_VERSION {
+        migration::migrate(doc, version).change_context(FlatFileTrackerError)?
+    } else {
+        doc
+    };
+
+    serde_json::from_value(doc)
         .change_context(FlatFileTrackerError)
-        .attach_printable("failed to deserialize lockfile")
+        .attach_printable("failed to deserialize database")
+}
+
+/// Refuses databases written by a newer release
+fn ensure_supported(version: u32) -> Result<(), FlatFileTrackerError> {
+    if version > migration::CURRENT_VERSION {
+        return Err(FlatFileTrackerError)
+            .attach_printable(format!(
+                "database version {version} is newer than the supported version {}",
+                migration::CURRENT_VERSION
+            ))
+            .attach(Suggestion(
+                "the database was written by a newer release; upgrade `track` to read it",
+            ));
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use assert_fs::{fixture::ChildPath, prelude::PathChild, TempDir};
+    use chrono::{DateTime, TimeDelta};
 
     use super::*;
 
@@ -192,6 +722,14 @@ mod tests {
         FlatFileTracker::new(db.to_path_buf(), lockfile.to_path_buf())
     }
 
+    fn record_between(start: DateTime<Utc>, end: DateTime<Utc>) -> TimeRecord {
+        TimeRecord::new(
+            StartTime::at(start),
+            EndTime::at(end),
+            SessionDetails::default(),
+        )
+    }
+
     #[test]
     fn is_running_returns_true_after_start() {
         let (_temp, db, lockfile) = temp_paths();
@@ -262,4 +800,651 @@ mod tests {
         // Then the `already_running` state is returned
         assert_eq!(started, StartupStatus::Running);
     }
+
+    #[test]
+    fn saving_a_smaller_database_leaves_no_trailing_data() {
+        // Given a database with two records
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        for _ in 0..2 {
+            tracker.start().unwrap();
+            tracker.stop().unwrap();
+        }
+
+        // When a database with a single record is saved over it
+        let mut smaller = load_database(&db, LockMode::Shared).unwrap();
+        smaller.records.truncate(1);
+        save_database(&db, &smaller).unwrap();
+
+        // Then the database only holds that record
+        assert_eq!(tracker.records().unwrap().count(), 1);
+    }
+
+    #[test]
+    fn previous_database_is_kept_as_backup() {
+        // Given a database with one record
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        tracker.start().unwrap();
+        tracker.stop().unwrap();
+
+        // When another record is saved
+        tracker.start().unwrap();
+        tracker.stop().unwrap();
+
+        // Then the backup holds the previous version
+        let backup = load_database(backup_path(&db), LockMode::Shared).unwrap();
+        assert_eq!(backup.records.len(), 1);
+    }
+
+    #[test]
+    fn corrupted_database_falls_back_to_backup() {
+        // Given a database with a backup
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        for _ in 0..2 {
+            tracker.start().unwrap();
+            tracker.stop().unwrap();
+        }
+
+        // When the database is corrupted
+        std::fs::write(&db, "{\"records\": [").unwrap();
+
+        // Then records are loaded from the backup
+        assert_eq!(tracker.records().unwrap().count(), 1);
+    }
+
+    #[test]
+    fn database_of_the_wrong_shape_falls_back_to_backup() {
+        // Given a database with a backup
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        for _ in 0..2 {
+            tracker.start().unwrap();
+            tracker.stop().unwrap();
+        }
+
+        // When the database is valid JSON but not a database
+        std::fs::write(&db, r#"{"records": 42}"#).unwrap();
+
+        // Then records are loaded from the backup
+        assert_eq!(tracker.records().unwrap().count(), 1);
+    }
+
+    #[test]
+    fn saving_over_corrupted_database_keeps_backup() {
+        // Given a corrupted database with a backup of one record
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        for _ in 0..2 {
+            tracker.start().unwrap();
+            tracker.stop().unwrap();
+        }
+        std::fs::write(&db, "{\"records\": [").unwrap();
+
+        // When a record is added and the database is corrupted again
+        let start = Utc::now() - TimeDelta::days(1);
+        tracker
+            .add_record(
+                record_between(start, start + TimeDelta::hours(1)),
+                Overlap::Allow,
+            )
+            .unwrap();
+        std::fs::write(&db, "{\"records\": [").unwrap();
+
+        // Then the backup still holds the record
+        assert_eq!(tracker.records().unwrap().count(), 1);
+    }
+
+    #[test]
+    fn corrupted_database_without_backup_is_an_error() {
+        // Given a corrupted database without a backup
+        let (_temp, db, lockfile) = temp_paths();
+        std::fs::write(&db, "not json").unwrap();
+        let tracker = new_flat_file_tracker(&db, &lockfile);
+
+        // When the records are loaded
+        let records = tracker.records();
+
+        // Then an error is returned
+        assert!(records.is_err());
+    }
+
+    #[test]
+    fn stop_fails_while_database_is_locked() {
+        // Given a running tracker
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker =
+            new_flat_file_tracker(&db, &lockfile).with_lock_timeout(Duration::from_millis(20));
+        tracker.start().unwrap();
+
+        // When another process holds the database lock
+        let _held =
+            FileLock::acquire(sibling_path(&db, "lock"), LockMode::Shared, Duration::ZERO).unwrap();
+
+        // Then the tracker cannot be stopped
+        assert!(tracker.stop().is_err());
+        assert!(tracker.is_running());
+    }
+
+    #[test]
+    fn unversioned_database_is_migrated_after_backup() {
+        // Given a database written before versioning
+        let (_temp, db, lockfile) = temp_paths();
+        std::fs::write(&db, r#"{"records":[]}"#).unwrap();
+
+        // When a session is tracked
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        tracker.start().unwrap();
+        tracker.stop().unwrap();
+
+        // Then the original is kept and the database is saved as the current version
+        let original = std::fs::read_to_string(sibling_path(&db, "v0.bak")).unwrap();
+        assert_eq!(original, r#"{"records":[]}"#);
+        let saved = load_database(&db, LockMode::Shared).unwrap();
+        assert_eq!(saved.version, migration::CURRENT_VERSION);
+        assert_eq!(saved.records.len(), 1);
+    }
+
+    #[test]
+    fn reading_an_unversioned_database_leaves_it_alone() {
+        // Given a database written before versioning
+        let (_temp, db, lockfile) = temp_paths();
+        std::fs::write(&db, r#"{"records":[]}"#).unwrap();
+        let tracker = new_flat_file_tracker(&db, &lockfile);
+
+        // When the records are only read
+        assert_eq!(tracker.records().unwrap().count(), 0);
+
+        // Then no backup is written under the shared lock
+        assert!(!sibling_path(&db, "v0.bak").exists());
+    }
+
+    #[test]
+    fn newer_database_version_is_refused() {
+        // Given a database written by a newer release
+        let (_temp, db, lockfile) = temp_paths();
+        std::fs::write(&db, r#"{"version":4294967295,"records":[]}"#).unwrap();
+        let tracker = new_flat_file_tracker(&db, &lockfile);
+
+        // When the records are loaded
+        let records = tracker.records();
+
+        // Then an error is returned
+        assert!(records.is_err());
+    }
+
+    #[test]
+    fn description_can_be_replaced_while_running() {
+        // Given a running tracker
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        tracker.start().unwrap();
+
+        // When the description is set and the tracker stopped
+        tracker.set_description("standup".to_string()).unwrap();
+        tracker.stop().unwrap();
+
+        // Then the record has that description
+        let record = tracker.records().unwrap().next().unwrap();
+        assert_eq!(record.description(), Some("standup"));
+    }
+
+    #[test]
+    fn description_cannot_be_set_when_not_running() {
+        // Given a new tracker
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+
+        // When a description is set
+        let result = tracker.set_description("standup".to_string());
+
+        // Then an error is returned
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn pause_and_resume_switch_between_paused_and_running() {
+        // Given a running tracker
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        tracker.start().unwrap();
+
+        // When the tracker is paused, then resumed
+        tracker.pause().unwrap();
+        let paused = tracker.state().unwrap();
+        tracker.resume().unwrap();
+
+        // Then it was paused and is running again
+        assert_eq!(paused, TrackerState::Paused);
+        assert!(tracker.is_running());
+    }
+
+    #[test]
+    fn pause_fails_when_not_running() {
+        // Given a new tracker
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+
+        // When the tracker is paused
+        let paused = tracker.pause();
+
+        // Then an error is returned
+        assert!(paused.is_err());
+        assert_eq!(tracker.state().unwrap(), TrackerState::Stopped);
+    }
+
+    #[test]
+    fn switch_leaves_no_gap_between_records() {
+        // Given a running tracker
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        tracker.start().unwrap();
+
+        // When switching to another project and stopping
+        let details = SessionDetails {
+            project: Some("foo".to_string()),
+            ..Default::default()
+        };
+        tracker.switch(details).unwrap();
+        tracker.stop().unwrap();
+
+        // Then the second record starts where the first one ended
+        let records = tracker.records().unwrap().collect::<Vec<_>>();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].end(), records[1].start());
+        assert_eq!(records[1].project(), Some("foo"));
+    }
+
+    #[test]
+    fn switch_starts_tracking_when_stopped() {
+        // Given a new tracker
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+
+        // When switching
+        tracker.switch(SessionDetails::default()).unwrap();
+
+        // Then the tracker is running without any record
+        assert!(tracker.is_running());
+        assert_eq!(tracker.records().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn cancel_discards_running_session() {
+        // Given a running tracker
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        tracker.start().unwrap();
+
+        // When cancelling
+        let discarded = tracker.cancel().unwrap();
+
+        // Then the session is gone without leaving a record
+        assert!(discarded.is_some());
+        assert!(!lockfile.exists());
+        assert_eq!(tracker.records().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn cancel_does_nothing_when_stopped() {
+        // Given a new tracker
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+
+        // When cancelling
+        let discarded = tracker.cancel().unwrap();
+
+        // Then nothing was discarded
+        assert_eq!(discarded, None);
+    }
+
+    #[test]
+    fn stop_before_start_is_refused() {
+        // Given a tracker started an hour ago
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        let start = Utc::now() - TimeDelta::hours(1);
+        tracker
+            .start_at(StartTime::at(start), SessionDetails::default())
+            .unwrap();
+
+        // When stopping it two hours ago
+        let end = Utc::now() - TimeDelta::hours(2);
+        let result = tracker.stop_at(EndTime::at(end), None);
+
+        // Then the session keeps running
+        assert!(result.is_err());
+        assert!(tracker.is_running());
+        assert_eq!(tracker.records().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn added_records_are_kept_in_order() {
+        // Given a record from today
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        let now = Utc::now();
+        tracker
+            .add_record(
+                record_between(now - TimeDelta::hours(1), now),
+                Overlap::Allow,
+            )
+            .unwrap();
+
+        // When adding a record from yesterday
+        let yesterday = now - TimeDelta::days(1);
+        let overlapping = tracker
+            .add_record(
+                record_between(yesterday, yesterday + TimeDelta::hours(1)),
+                Overlap::Allow,
+            )
+            .unwrap();
+
+        // Then it comes first and overlaps nothing
+        assert!(overlapping.is_empty());
+        let records = tracker.records().unwrap().collect::<Vec<_>>();
+        assert_eq!(records[0].start(), yesterday);
+    }
+
+    #[test]
+    fn overlapping_record_is_reported_or_refused() {
+        // Given a record from 9 to 10
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        let nine = Utc::now() - TimeDelta::hours(3);
+        tracker
+            .add_record(
+                record_between(nine, nine + TimeDelta::hours(1)),
+                Overlap::Allow,
+            )
+            .unwrap();
+        let half_past_nine = nine + TimeDelta::minutes(30);
+        let meeting = record_between(half_past_nine, half_past_nine + TimeDelta::hours(1));
+
+        // When adding an overlapping record
+        let refused = tracker.add_record(meeting.clone(), Overlap::Refuse);
+        let allowed = tracker.add_record(meeting, Overlap::Allow).unwrap();
+
+        // Then it is refused if asked to, otherwise added with a report
+        assert!(refused.is_err());
+        assert_eq!(allowed.len(), 1);
+        assert_eq!(tracker.records().unwrap().count(), 2);
+    }
+
+    #[test]
+    fn record_overlapping_running_session_is_refused() {
+        // Given a session running for an hour
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        let started = Utc::now() - TimeDelta::hours(1);
+        tracker
+            .start_at(StartTime::at(started), SessionDetails::default())
+            .unwrap();
+
+        // When adding a record from half an hour ago
+        let meeting = record_between(started + TimeDelta::minutes(30), Utc::now());
+        let refused = tracker.add_record(meeting, Overlap::Refuse);
+
+        // Then it is refused
+        assert!(refused.is_err());
+        assert_eq!(tracker.records().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn record_ending_before_it_starts_is_rejected() {
+        // Given a new tracker
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+
+        // When adding a record ending before it starts
+        let now = Utc::now();
+        let result = tracker.add_record(
+            record_between(now, now - TimeDelta::hours(1)),
+            Overlap::Allow,
+        );
+
+        // Then nothing is added
+        assert!(result.is_err());
+        assert_eq!(tracker.records().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn edit_cannot_end_record_before_its_start() {
+        // Given a record from an hour ago until now
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        let now = Utc::now();
+        let record = record_between(now - TimeDelta::hours(1), now);
+        let id = record.id();
+        tracker.add_record(record, Overlap::Allow).unwrap();
+
+        // When moving its end two hours back
+        let edit = RecordEdit {
+            end: Some(EndTime::at(now - TimeDelta::hours(2))),
+            ..Default::default()
+        };
+        let result = tracker.edit_record(id, edit);
+
+        // Then the record is unchanged
+        assert!(result.is_err());
+        let stored = tracker.records().unwrap().next().unwrap();
+        assert_eq!(stored.end(), now);
+    }
+
+    #[test]
+    fn deleted_record_is_gone() {
+        // Given two records
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        let now = Utc::now();
+        let first = record_between(now - TimeDelta::hours(2), now - TimeDelta::hours(1));
+        let id = first.id();
+        tracker.add_record(first, Overlap::Allow).unwrap();
+        tracker
+            .add_record(
+                record_between(now - TimeDelta::hours(1), now),
+                Overlap::Allow,
+            )
+            .unwrap();
+
+        // When deleting the first
+        let deleted = tracker.delete_record(id).unwrap();
+
+        // Then only the second is left
+        assert_eq!(deleted.id(), id);
+        let ids = tracker
+            .records()
+            .unwrap()
+            .map(|r| r.id())
+            .collect::<Vec<_>>();
+        assert_eq!(ids.len(), 1);
+        assert!(!ids.contains(&id));
+    }
+
+    #[test]
+    fn undo_stop_resumes_session() {
+        // Given a stopped session
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        tracker.start().unwrap();
+        tracker.stop().unwrap();
+
+        // When the stop is undone
+        let undone = tracker.undo().unwrap();
+
+        // Then the session is running again and nothing is recorded
+        assert_eq!(undone.as_deref(), Some("stop"));
+        assert!(tracker.is_running());
+        assert_eq!(tracker.records().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn redo_applies_undone_command_again() {
+        // Given an undone stop
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        tracker.start().unwrap();
+        tracker.stop().unwrap();
+        tracker.undo().unwrap();
+
+        // When it is redone
+        let redone = tracker.redo().unwrap();
+
+        // Then the session is recorded again
+        assert_eq!(redone.as_deref(), Some("stop"));
+        assert!(!tracker.is_running());
+        assert_eq!(tracker.records().unwrap().count(), 1);
+        assert!(tracker.redo().unwrap().is_none());
+    }
+
+    #[test]
+    fn undo_edit_restores_record() {
+        // Given an edited record
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        let start = Utc::now() - TimeDelta::hours(2);
+        let record = record_between(start, start + TimeDelta::hours(1));
+        tracker.add_record(record.clone(), Overlap::Allow).unwrap();
+        let edit = RecordEdit {
+            project: Some(Some("acme".to_string())),
+            ..Default::default()
+        };
+        tracker.edit_record(record.id(), edit).unwrap();
+
+        // When the edit is undone
+        tracker.undo().unwrap();
+
+        // Then the record is as before
+        let records: Vec<_> = tracker.records().unwrap().collect();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id(), record.id());
+        assert_eq!(records[0].project(), None);
+    }
+
+    #[test]
+    fn applied_changes_are_undone_together() {
+        // Given two records
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        let now = Utc::now();
+        let first = record_between(now - TimeDelta::hours(3), now - TimeDelta::hours(2));
+        let second = record_between(now - TimeDelta::hours(2), now - TimeDelta::hours(1));
+        tracker.add_record(first.clone(), Overlap::Allow).unwrap();
+        tracker.add_record(second.clone(), Overlap::Allow).unwrap();
+
+        // When editing the first, deleting the second and adding a third at once
+        let edit = RecordEdit {
+            project: Some(Some("acme".to_string())),
+            ..Default::default()
+        };
+        tracker
+            .apply_changes(RecordChanges {
+                edits: vec![(first.id(), edit)],
+                deletions: vec![second.id()],
+                additions: vec![record_between(now - TimeDelta::minutes(30), now)],
+            })
+            .unwrap();
+        let changed = tracker.records().unwrap().collect::<Vec<_>>();
+        tracker.undo().unwrap();
+
+        // Then a single undo brings back both records as they were
+        assert_eq!(changed.len(), 2);
+        assert_eq!(changed[0].project(), Some("acme"));
+        let records = tracker.records().unwrap().collect::<Vec<_>>();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id(), first.id());
+        assert_eq!(records[0].project(), None);
+        assert_eq!(records[1].id(), second.id());
+    }
+
+    #[test]
+    fn changes_overlapping_stored_records_are_refused() {
+        // Given two records an hour apart
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        let now = Utc::now();
+        let first = record_between(now - TimeDelta::hours(4), now - TimeDelta::hours(3));
+        let second = record_between(now - TimeDelta::hours(2), now - TimeDelta::hours(1));
+        tracker.add_record(first.clone(), Overlap::Allow).unwrap();
+        tracker.add_record(second.clone(), Overlap::Allow).unwrap();
+
+        // When extending the first into the second and adding another record
+        let edit = RecordEdit {
+            end: Some(EndTime(now - TimeDelta::minutes(90))),
+            ..Default::default()
+        };
+        let result = tracker.apply_changes(RecordChanges {
+            edits: vec![(first.id(), edit)],
+            deletions: Vec::default(),
+            additions: vec![record_between(now - TimeDelta::minutes(30), now)],
+        });
+
+        // Then nothing is changed
+        assert!(result.is_err());
+        let records = tracker.records().unwrap().collect::<Vec<_>>();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].end(), first.end());
+    }
+
+    #[test]
+    fn undo_with_empty_history_does_nothing() {
+        // Given a new tracker
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+
+        // When undoing
+        let undone = tracker.undo().unwrap();
+
+        // Then there was nothing to undo
+        assert!(undone.is_none());
+    }
+
+    #[test]
+    fn undo_refuses_database_changed_by_hand() {
+        // Given an added record that was removed from the file by hand
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        let start = Utc::now() - TimeDelta::hours(2);
+        let record = record_between(start, start + TimeDelta::hours(1));
+        tracker.add_record(record, Overlap::Allow).unwrap();
+        save_database(&db, &FlatFileDatabase::default()).unwrap();
+
+        // When the addition is undone
+        let result = tracker.undo();
+
+        // Then undoing fails, keeping the change in the history
+        assert!(result.is_err());
+        assert!(tracker.undo().is_err());
+    }
+
+    #[test]
+    fn undo_refuses_to_replace_a_different_session() {
+        // Given a noted session, replaced by another one outside of the history
+        let (_temp, db, lockfile) = temp_paths();
+        let mut tracker = new_flat_file_tracker(&db, &lockfile);
+        tracker
+            .start_with(SessionDetails {
+                project: Some("acme".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        tracker.set_description("planning".to_string()).unwrap();
+        let other = LockfileData::new(
+            StartTime::now(),
+            SessionDetails {
+                project: Some("initech".to_string()),
+                ..Default::default()
+            },
+        );
+        lockfile::write(&lockfile, &other).unwrap();
+
+        // When the note is undone
+        let result = tracker.undo();
+
+        // Then undoing fails and the other session keeps running
+        assert!(result.is_err());
+        let running = tracker.current_session().unwrap().unwrap();
+        assert_eq!(running, other);
+    }
 }
@@ -0,0 +1,173 @@
+//! The undo history of a flat-file database
+//!
+//! Every mutating command is remembered as the records it removed and added
+//! and the running session before and after it. Undoing a command applies
+//! the inverse change, redoing it applies the change again. Only the most
+//! recent commands are kept, next to the database in a `.history` file.
+
+use std::path::Path;
+
+use error_stack::{Result, ResultExt};
+use serde::{Deserialize, Serialize};
+
+use crate::feature::tracker::{atomic, lockfile::LockfileData, TimeRecord};
+
+/// Number of commands that can be undone
+pub const HISTORY_LEN: usize = 10;
+
+#[derive(Debug, thiserror::Error)]
+#[error("undo history error")]
+pub struct HistoryError;
+
+/// What a single command changed
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Change {
+    /// Name of the command, e.g. `stop`
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<TimeRecord>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<TimeRecord>,
+    /// Missing if the command left the running session alone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<SessionChange>,
+}
+
+/// The running session before and after a command, `None` when stopped
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SessionChange {
+    pub before: Option<LockfileData>,
+    pub after: Option<LockfileData>,
+}
+
+impl Change {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            removed: Vec::default(),
+            added: Vec::default(),
+            session: None,
+        }
+    }
+
+    /// Sets the records the command removed and added
+    pub fn with_records(mut self, removed: Vec<TimeRecord>, added: Vec<TimeRecord>) -> Self {
+        self.removed = removed;
+        self.added = added;
+        self
+    }
+
+    /// Sets the running session before and after the command
+    pub fn with_session(
+        mut self,
+        before: Option<LockfileData>,
+        after: Option<LockfileData>,
+    ) -> Self {
+        self.session = Some(SessionChange { before, after });
+        self
+    }
+
+    /// The change reverting this one
+    pub fn inverse(self) -> Self {
+        Self {
+            command: self.command,
+            removed: self.added,
+            added: self.removed,
+            session: self.session.map(|session| SessionChange {
+                before: session.after,
+                after: session.before,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct History {
+    /// Commands that can be undone, most recent last
+    undo: Vec<Change>,
+    /// Commands that were undone, most recently undone last
+    redo: Vec<Change>,
+}
+
+impl History {
+    /// Remembers a new command, which makes undone commands unredoable
+    pub fn record(&mut self, change: Change) {
+        self.redo.clear();
+        self.push_undo(change);
+    }
+
+    pub fn pop_undo(&mut self) -> Option<Change> {
+        self.undo.pop()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<Change> {
+        self.redo.pop()
+    }
+
+    pub fn push_undo(&mut self, change: Change) {
+        self.undo.push(change);
+        self.undo
+            .drain(..self.undo.len().saturating_sub(HISTORY_LEN));
+    }
+
+    pub fn push_redo(&mut self, change: Change) {
+        self.redo.push(change);
+    }
+}
+
+pub fn load(path: &Path) -> Result<History, HistoryError> {
+    if !path.exists() {
+        return Ok(History::default());
+    }
+    let buf = std::fs::read_to_string(path)
+        .change_context(HistoryError)
+        .attach_printable("failed to read undo history")?;
+    serde_json::from_str(&buf)
+        .change_context(HistoryError)
+        .attach_printable("failed to deserialize undo history")
+}
+
+pub fn save(path: &Path, history: &History) -> Result<(), HistoryError> {
+    let buf = serde_json::to_string(history)
+        .change_context(HistoryError)
+        .attach_printable("failed to serialize undo history")?;
+    atomic::replace(path, buf.as_bytes())
+        .change_context(HistoryError)
+        .attach_printable("failed to write undo history")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_recent_commands_are_kept() {
+        // Given a history of as many commands as are kept
+        let mut history = History::default();
+        for _ in 0..HISTORY_LEN {
+            history.record(Change::new("add"));
+        }
+
+        // When another command is recorded
+        history.record(Change::new("stop"));
+
+        // Then the oldest one is forgotten
+        assert_eq!(history.undo.len(), HISTORY_LEN);
+        assert_eq!(history.pop_undo().unwrap().command, "stop");
+    }
+
+    #[test]
+    fn new_command_clears_redo() {
+        // Given an undone command
+        let mut history = History::default();
+        history.record(Change::new("add"));
+        let change = history.pop_undo().unwrap();
+        history.push_redo(change);
+
+        // When another command is recorded
+        history.record(Change::new("start"));
+
+        // Then the undone command can no longer be redone
+        assert!(history.pop_redo().is_none());
+    }
+}
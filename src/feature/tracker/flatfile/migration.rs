@@ -0,0 +1,135 @@
+//! Upgrades older flat-file databases to the current format
+//!
+//! Every migration takes the raw JSON document of one version and returns
+//! the document of the next one, so a database of any older version is
+//! upgraded by running the migrations after it in order.
+
+use chrono::{DateTime, Utc};
+use error_stack::{Result, ResultExt};
+use serde_json::Value;
+
+use crate::feature::tracker::RecordId;
+
+/// Version of the database format written by this binary
+pub const CURRENT_VERSION: u32 = 2;
+
+type Migration = fn(Value) -> Result<Value, MigrationError>;
+
+/// Migrations indexed by the version they upgrade from
+const MIGRATIONS: &[Migration] = &[v0_to_v1, v1_to_v2];
+
+const _: () = assert!(MIGRATIONS.len() == CURRENT_VERSION as usize);
+
+#[derive(Debug, thiserror::Error)]
+#[error("database migration error")]
+pub struct MigrationError;
+
+/// Reads the format version of a database document
+///
+/// Documents written before versioning was introduced have no version field
+/// and are version 0.
+pub fn version_of(doc: &Value) -> Result<u32, MigrationError> {
+    match doc.get("version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or(MigrationError)
+            .attach_printable_lazy(|| format!("invalid database version: {version}")),
+    }
+}
+
+/// Upgrades a document of version `from` to [`CURRENT_VERSION`]
+pub fn migrate(mut doc: Value, from: u32) -> Result<Value, MigrationError> {
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        doc = migration(doc)
+            .attach_printable_lazy(|| format!("failed to migrate from version {version}"))?;
+    }
+    Ok(doc)
+}
+
+/// Adds the version marker
+fn v0_to_v1(mut doc: Value) -> Result<Value, MigrationError> {
+    doc.as_object_mut()
+        .ok_or(MigrationError)
+        .attach_printable("database is not a JSON object")?
+        .insert("version".to_string(), Value::from(1));
+    Ok(doc)
+}
+
+/// Gives every record an identifier
+fn v1_to_v2(mut doc: Value) -> Result<Value, MigrationError> {
+    let records = doc
+        .get_mut("records")
+        .and_then(Value::as_array_mut)
+        .ok_or(MigrationError)
+        .attach_printable("database has no list of records")?;
+
+    for (position, record) in records.iter_mut().enumerate() {
+        let start = record
+            .get("start")
+            .and_then(Value::as_str)
+            .and_then(|start| start.parse::<DateTime<Utc>>().ok())
+            .ok_or(MigrationError)
+            .attach_printable_lazy(|| format!("record {position} has no valid start time"))?;
+        let id = RecordId::legacy(start.timestamp_millis(), position as u64);
+        record
+            .as_object_mut()
+            .ok_or(MigrationError)
+            .attach_printable_lazy(|| format!("record {position} is not a JSON object"))?
+            .insert("id".to_string(), Value::from(id.to_string()));
+    }
+
+    doc["version"] = Value::from(2);
+    Ok(doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn unversioned_document_is_version_zero() {
+        let doc = json!({ "records": [] });
+
+        assert_eq!(version_of(&doc).unwrap(), 0);
+    }
+
+    #[test]
+    fn migrating_unversioned_document_reaches_current_version() {
+        let doc = json!({ "records": [] });
+
+        let doc = migrate(doc, 0).unwrap();
+
+        assert_eq!(version_of(&doc).unwrap(), CURRENT_VERSION);
+        assert_eq!(doc["records"], json!([]));
+    }
+
+    #[test]
+    fn current_document_is_left_untouched() {
+        let doc = json!({ "version": CURRENT_VERSION, "records": [] });
+
+        let migrated = migrate(doc.clone(), CURRENT_VERSION).unwrap();
+
+        assert_eq!(migrated, doc);
+    }
+
+    #[test]
+    fn records_get_stable_identifiers() {
+        let doc = json!({
+            "version": 1,
+            "records": [
+                { "start": "2026-10-17T09:00:00Z", "end": "2026-10-17T10:00:00Z" },
+                { "start": "2026-10-17T09:00:00Z", "end": "2026-10-17T11:00:00Z" },
+            ],
+        });
+
+        let first = migrate(doc.clone(), 1).unwrap();
+        let second = migrate(doc, 1).unwrap();
+
+        assert_eq!(first, second);
+        assert_ne!(first["records"][0]["id"], first["records"][1]["id"]);
+    }
+}
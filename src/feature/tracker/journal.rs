@@ -0,0 +1,736 @@
+//! An append-only journal tracker
+
+// journal tracker
+// - "lockfile": tracker is running
+// - "journal file": one JSON event per line, replayed on load
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::Utc;
+use error_stack::{Result, ResultExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Suggestion,
+    feature::tracker::{
+        atomic::{self, sibling_path},
+        check_new_record,
+        lock::{FileLock, LockMode},
+        lockfile::{self, LockfileData},
+        unknown_record, EndTime, Overlap, PauseStatus, RecordChanges, RecordEdit, RecordId,
+        Reporter, ResumeStatus, SessionDetails, StartTime, StartupStatus, TimeRecord, Tracker,
+        TrackerError, TrackerState, DEFAULT_LOCK_TIMEOUT,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+#[error("journal tracker error")]
+pub struct JournalTrackerError;
+
+/// A single line of the journal
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum JournalEntry {
+    /// A finished session
+    Record(TimeRecord),
+    /// A record replacing the earlier one with the same identifier
+    Edit(TimeRecord),
+    /// The removal of a record
+    Delete { id: RecordId },
+}
+
+pub struct JournalTracker {
+    journal: PathBuf,
+    lockfile: PathBuf,
+    lock_timeout: Duration,
+}
+
+impl JournalTracker {
+    pub fn new<J, L>(journal: J, lockfile: L) -> Self
+    where
+        J: Into<PathBuf>,
+        L: Into<PathBuf>,
+    {
+        let journal = journal.into();
+        let lockfile = lockfile.into();
+        Self {
+            journal,
+            lockfile,
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+        }
+    }
+
+    /// Sets how long to wait for other processes to release the journal
+    pub fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = timeout;
+        self
+    }
+
+    /// Rewrites the journal as a snapshot holding one line per record
+    pub fn compact(&mut self) -> Result<(), TrackerError> {
+        self.compact_impl().change_context(TrackerError)
+    }
+
+    /// Acquires the advisory lock guarding the journal and lockfile
+    fn lock(&self, mode: LockMode) -> Result<FileLock, JournalTrackerError> {
+        FileLock::acquire(sibling_path(&self.journal, "lock"), mode, self.lock_timeout)
+            .change_context(JournalTrackerError)
+            .attach_printable("failed to lock journal")
+            .attach(Suggestion(
+                "another `track` process is using the journal; \
+                 try again or raise the lock timeout with --lock-timeout",
+            ))
+    }
+
+    fn start_impl(
+        &self,
+        start: StartTime,
+        details: SessionDetails,
+    ) -> Result<StartupStatus, JournalTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let session = LockfileData::new(start, details);
+        lockfile::create(&self.lockfile, &session).change_context(JournalTrackerError)
+    }
+
+    fn stop_impl(
+        &self,
+        end: EndTime,
+        description: Option<String>,
+    ) -> Result<(), JournalTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let mut session = lockfile::read(&self.lockfile).change_context(JournalTrackerError)?;
+        if let Some(description) = description {
+            session.details.append_description(description);
+        }
+        let record = session.finish_at(end).change_context(JournalTrackerError)?;
+        append_entry(&self.journal, &JournalEntry::Record(record))?;
+
+        lockfile::remove(&self.lockfile).change_context(JournalTrackerError)
+    }
+
+    fn set_description_impl(&self, description: String) -> Result<(), JournalTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        lockfile::update(&self.lockfile, |session| {
+            session.details.description = Some(description);
+        })
+        .change_context(JournalTrackerError)
+    }
+
+    fn cancel_impl(&self) -> Result<Option<Duration>, JournalTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        if !self.lockfile.exists() {
+            return Ok(None);
+        }
+        let session = lockfile::read(&self.lockfile).change_context(JournalTrackerError)?;
+        lockfile::remove(&self.lockfile).change_context(JournalTrackerError)?;
+
+        Ok(Some(session.finish(EndTime::now()).duration()))
+    }
+
+    fn add_record_impl(
+        &self,
+        record: TimeRecord,
+        overlap: Overlap,
+    ) -> Result<Vec<TimeRecord>, JournalTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let running = lockfile::session(&self.lockfile)
+            .change_context(JournalTrackerError)?
+            .map(|session| session.snapshot(Utc::now()));
+        let existing = replay(&self.journal)?.into_iter().chain(running);
+        let overlapping =
+            check_new_record(&record, existing, overlap).change_context(JournalTrackerError)?;
+        append_entry(&self.journal, &JournalEntry::Record(record))?;
+
+        Ok(overlapping)
+    }
+
+    fn edit_record_impl(
+        &self,
+        id: RecordId,
+        edit: RecordEdit,
+    ) -> Result<TimeRecord, JournalTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let record = replay(&self.journal)?
+            .into_iter()
+            .find(|record| record.id == id)
+            .ok_or_else(|| unknown_record(id))
+            .change_context(JournalTrackerError)?;
+        let record = edit.apply(record).change_context(JournalTrackerError)?;
+        append_entry(&self.journal, &JournalEntry::Edit(record.clone()))?;
+
+        Ok(record)
+    }
+
+    fn delete_record_impl(&self, id: RecordId) -> Result<TimeRecord, JournalTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let record = replay(&self.journal)?
+            .into_iter()
+            .find(|record| record.id == id)
+            .ok_or_else(|| unknown_record(id))
+            .change_context(JournalTrackerError)?;
+        append_entry(&self.journal, &JournalEntry::Delete { id })?;
+
+        Ok(record)
+    }
+
+    fn apply_changes_impl(&self, changes: RecordChanges) -> Result<(), JournalTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let records = replay(&self.journal)?;
+        let changes = changes
+            .resolve(records.clone())
+            .change_context(JournalTrackerError)?;
+        let running = lockfile::session(&self.lockfile)
+            .change_context(JournalTrackerError)?
+            .map(|session| session.snapshot(Utc::now()));
+        changes
+            .check_overlaps(records.into_iter().chain(running))
+            .change_context(JournalTrackerError)?;
+
+        let entries = changes
+            .deleted
+            .iter()
+            .map(|record| JournalEntry::Delete { id: record.id })
+            .chain(
+                changes
+                    .edited
+                    .into_iter()
+                    .map(|(_, after)| JournalEntry::Edit(after)),
+            )
+            .chain(changes.added.into_iter().map(JournalEntry::Record))
+            .collect::<Vec<_>>();
+        append_entries(&self.journal, &entries)
+    }
+
+    fn switch_impl(&self, details: SessionDetails) -> Result<(), JournalTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let now = Utc::now();
+        if self.lockfile.exists() {
+            let session = lockfile::read(&self.lockfile).change_context(JournalTrackerError)?;
+            let record = session.finish(EndTime(now));
+            append_entry(&self.journal, &JournalEntry::Record(record))?;
+        }
+
+        let session = LockfileData::new(StartTime(now), details);
+        lockfile::write(&self.lockfile, &session).change_context(JournalTrackerError)
+    }
+
+    fn pause_impl(&self) -> Result<PauseStatus, JournalTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        lockfile::update(&self.lockfile, |session| session.pause(Utc::now()))
+            .change_context(JournalTrackerError)
+    }
+
+    fn resume_impl(&self) -> Result<ResumeStatus, JournalTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        lockfile::update(&self.lockfile, |session| session.resume(Utc::now()))
+            .change_context(JournalTrackerError)
+    }
+
+    fn state_impl(&self) -> Result<TrackerState, JournalTrackerError> {
+        let _lock = self.lock(LockMode::Shared)?;
+
+        lockfile::state(&self.lockfile).change_context(JournalTrackerError)
+    }
+
+    fn current_session_impl(&self) -> Result<Option<LockfileData>, JournalTrackerError> {
+        let _lock = self.lock(LockMode::Shared)?;
+
+        lockfile::session(&self.lockfile).change_context(JournalTrackerError)
+    }
+
+    fn records_impl(&self) -> Result<Vec<TimeRecord>, JournalTrackerError> {
+        let _lock = self.lock(LockMode::Shared)?;
+
+        replay(&self.journal)
+    }
+
+    fn compact_impl(&self) -> Result<(), JournalTrackerError> {
+        let _lock = self.lock(LockMode::Exclusive)?;
+
+        let mut snapshot = String::new();
+        for record in replay(&self.journal)? {
+            let line = serde_json::to_string(&JournalEntry::Record(record))
+                .change_context(JournalTrackerError)
+                .attach_printable("failed to serialize journal entry")?;
+            snapshot.push_str(&line);
+            snapshot.push('\n');
+        }
+
+        atomic::backup(&self.journal)
+            .and_then(|()| atomic::replace(&self.journal, snapshot.as_bytes()))
+            .change_context(JournalTrackerError)
+            .attach_printable("failed to write compacted journal")
+    }
+}
+
+impl Tracker for JournalTracker {
+    fn start_at(
+        &mut self,
+        start: StartTime,
+        details: SessionDetails,
+    ) -> Result<StartupStatus, TrackerError> {
+        self.start_impl(start, details).change_context(TrackerError)
+    }
+
+    fn stop_at(&mut self, end: EndTime, description: Option<String>) -> Result<(), TrackerError> {
+        self.stop_impl(end, description)
+            .change_context(TrackerError)
+    }
+
+    fn set_description(&mut self, description: String) -> Result<(), TrackerError> {
+        self.set_description_impl(description)
+            .change_context(TrackerError)
+    }
+
+    fn cancel(&mut self) -> Result<Option<Duration>, TrackerError> {
+        self.cancel_impl().change_context(TrackerError)
+    }
+
+    fn add_record(
+        &mut self,
+        record: TimeRecord,
+        overlap: Overlap,
+    ) -> Result<Vec<TimeRecord>, TrackerError> {
+        self.add_record_impl(record, overlap)
+            .change_context(TrackerError)
+    }
+
+    fn edit_record(&mut self, id: RecordId, edit: RecordEdit) -> Result<TimeRecord, TrackerError> {
+        self.edit_record_impl(id, edit).change_context(TrackerError)
+    }
+
+    fn delete_record(&mut self, id: RecordId) -> Result<TimeRecord, TrackerError> {
+        self.delete_record_impl(id).change_context(TrackerError)
+    }
+
+    fn apply_changes(&mut self, changes: RecordChanges) -> Result<(), TrackerError> {
+        self.apply_changes_impl(changes)
+            .change_context(TrackerError)
+    }
+
+    fn switch(&mut self, details: SessionDetails) -> Result<(), TrackerError> {
+        self.switch_impl(details).change_context(TrackerError)
+    }
+
+    fn pause(&mut self) -> Result<PauseStatus, TrackerError> {
+        self.pause_impl().change_context(TrackerError)
+    }
+
+    fn resume(&mut self) -> Result<ResumeStatus, TrackerError> {
+        self.resume_impl().change_context(TrackerError)
+    }
+
+    fn state(&self) -> Result<TrackerState, TrackerError> {
+        self.state_impl().change_context(TrackerError)
+    }
+
+    fn current_session(&self) -> Result<Option<LockfileData>, TrackerError> {
+        self.current_session_impl().change_context(TrackerError)
+    }
+
+    fn records(&self) -> Result<impl Iterator<Item = TimeRecord>, TrackerError> {
+        let records = self.records_impl().change_context(TrackerError)?;
+        Ok(records.into_iter())
+    }
+}
+
+impl Reporter for JournalTracker {}
+
+/// Appends a single event to the journal
+fn append_entry<P>(journal: P, entry: &JournalEntry) -> Result<(), JournalTrackerError>
+where
+    P: AsRef<Path>,
+{
+    append_entries(journal, std::slice::from_ref(entry))
+}
+
+/// Appends `entries` with a single write, so they land together
+fn append_entries<P>(journal: P, entries: &[JournalEntry]) -> Result<(), JournalTrackerError>
+where
+    P: AsRef<Path>,
+{
+    let mut lines = String::new();
+    for entry in entries {
+        lines += &serde_json::to_string(entry)
+            .change_context(JournalTrackerError)
+            .attach_printable("failed to serialize journal entry")?;
+        lines.push('\n');
+    }
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(journal.as_ref())
+        .change_context(JournalTrackerError)
+        .attach_printable("failed to open journal")?;
+    discard_partial_line(&mut file)?;
+
+    file.write_all(lines.as_bytes())
+        .change_context(JournalTrackerError)
+        .attach_printable("failed to append to journal")?;
+    file.sync_data()
+        .change_context(JournalTrackerError)
+        .attach_printable("failed to sync journal")
+}
+
+/// Truncates an unterminated last line left behind by an interrupted append
+fn discard_partial_line(file: &mut File) -> Result<(), JournalTrackerError> {
+    let len = file
+        .metadata()
+        .change_context(JournalTrackerError)
+        .attach_printable("failed to read journal metadata")?
+        .len();
+    if len == 0 {
+        return Ok(());
+    }
+
+    let mut last = [0u8];
+    file.seek(SeekFrom::Start(len - 1))
+        .and_then(|_| file.read_exact(&mut last))
+        .change_context(JournalTrackerError)
+        .attach_printable("failed to read end of journal")?;
+    if last[0] == b'\n' {
+        return Ok(());
+    }
+
+    let mut contents = Vec::new();
+    file.seek(SeekFrom::Start(0))
+        .and_then(|_| file.read_to_end(&mut contents))
+        .change_context(JournalTrackerError)
+        .attach_printable("failed to read journal")?;
+    let keep = contents
+        .iter()
+        .rposition(|&byte| byte == b'\n')
+        .map_or(0, |newline| newline + 1);
+    tracing::warn!(
+        bytes = len - keep as u64,
+        "discarding partially written journal entry"
+    );
+    file.set_len(keep as u64)
+        .change_context(JournalTrackerError)
+        .attach_printable("failed to truncate journal")
+}
+
+/// Rebuilds the list of records by replaying every journal event
+fn replay<P>(journal: P) -> Result<Vec<TimeRecord>, JournalTrackerError>
+where
+    P: AsRef<Path>,
+{
+    let file = match File::open(journal.as_ref()) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(e)
+                .change_context(JournalTrackerError)
+                .attach_printable("failed to open journal")
+        }
+    };
+
+    let mut records = Vec::new();
+    let mut lines = BufReader::new(file).lines().enumerate().peekable();
+    while let Some((index, line)) = lines.next() {
+        let line = line
+            .change_context(JournalTrackerError)
+            .attach_printable("failed to read journal")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str(&line) {
+            Ok(JournalEntry::Record(mut record)) => {
+                // entries appended before records had identifiers
+                if record.id.is_nil() {
+                    record.id = RecordId::legacy(record.start.timestamp_millis(), index as u64);
+                }
+                records.push(record);
+            }
+            Ok(JournalEntry::Edit(record)) => {
+                match records.iter_mut().find(|other| other.id == record.id) {
+                    Some(other) => *other = record,
+                    None => {
+                        tracing::warn!(line = index + 1, id = %record.id, "ignoring edit of unknown record")
+                    }
+                }
+            }
+            Ok(JournalEntry::Delete { id }) => records.retain(|record| record.id != id),
+            // only the final line can be damaged by an interrupted append
+            Err(_) if lines.peek().is_none() => {
+                tracing::warn!(line = index + 1, "ignoring truncated journal entry");
+            }
+            Err(e) => {
+                return Err(e)
+                    .change_context(JournalTrackerError)
+                    .attach_printable_lazy(|| {
+                        format!("malformed journal entry on line {}", index + 1)
+                    })
+                    .attach(Suggestion(
+                        "the journal is corrupted; restore it from the '.bak' file \
+                         or remove the damaged line by hand",
+                    ));
+            }
+        }
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{fixture::ChildPath, prelude::PathChild, TempDir};
+
+    use super::*;
+
+    fn temp_paths() -> (TempDir, ChildPath, ChildPath) {
+        let temp = TempDir::new().unwrap();
+        let journal = temp.child("records.jsonl");
+        let lockfile = temp.child("lockfile");
+        (temp, journal, lockfile)
+    }
+
+    fn new_journal_tracker(journal: &ChildPath, lockfile: &ChildPath) -> JournalTracker {
+        JournalTracker::new(journal.to_path_buf(), lockfile.to_path_buf())
+    }
+
+    fn track_sessions(tracker: &mut JournalTracker, count: usize) {
+        for _ in 0..count {
+            tracker.start().unwrap();
+            tracker.stop().unwrap();
+        }
+    }
+
+    #[test]
+    fn each_stop_appends_one_line() {
+        // Given a new tracker
+        let (_temp, journal, lockfile) = temp_paths();
+        let mut tracker = new_journal_tracker(&journal, &lockfile);
+
+        // When two sessions are tracked
+        track_sessions(&mut tracker, 2);
+
+        // Then the journal holds two lines
+        let contents = std::fs::read_to_string(&journal).unwrap();
+        assert_eq!(contents.lines().count(), 2);
+        assert_eq!(tracker.records().unwrap().count(), 2);
+    }
+
+    #[test]
+    fn truncated_trailing_line_is_ignored() {
+        // Given a journal whose last append was interrupted
+        let (_temp, journal, lockfile) = temp_paths();
+        let mut tracker = new_journal_tracker(&journal, &lockfile);
+        track_sessions(&mut tracker, 1);
+        let mut file = OpenOptions::new().append(true).open(&journal).unwrap();
+        file.write_all(br#"{"event":"record","sta"#).unwrap();
+
+        // When the records are loaded
+        let records = tracker.records().unwrap();
+
+        // Then only the complete record is returned
+        assert_eq!(records.count(), 1);
+    }
+
+    #[test]
+    fn append_after_truncated_line_keeps_journal_readable() {
+        // Given a journal whose last append was interrupted
+        let (_temp, journal, lockfile) = temp_paths();
+        let mut tracker = new_journal_tracker(&journal, &lockfile);
+        track_sessions(&mut tracker, 1);
+        let mut file = OpenOptions::new().append(true).open(&journal).unwrap();
+        file.write_all(br#"{"event":"record","sta"#).unwrap();
+
+        // When another session is tracked
+        track_sessions(&mut tracker, 1);
+
+        // Then both complete records are returned
+        assert_eq!(tracker.records().unwrap().count(), 2);
+    }
+
+    #[test]
+    fn malformed_line_in_the_middle_is_an_error() {
+        // Given a journal with a damaged line followed by a valid one
+        let (_temp, journal, lockfile) = temp_paths();
+        let mut tracker = new_journal_tracker(&journal, &lockfile);
+        track_sessions(&mut tracker, 1);
+        let valid = std::fs::read_to_string(&journal).unwrap();
+        std::fs::write(&journal, format!("garbage\n{valid}")).unwrap();
+
+        // When the records are loaded
+        let records = tracker.records();
+
+        // Then an error is returned
+        assert!(records.is_err());
+    }
+
+    #[test]
+    fn compaction_keeps_records() {
+        // Given a journal with a truncated trailing line
+        let (_temp, journal, lockfile) = temp_paths();
+        let mut tracker = new_journal_tracker(&journal, &lockfile);
+        track_sessions(&mut tracker, 3);
+        let mut file = OpenOptions::new().append(true).open(&journal).unwrap();
+        file.write_all(b"{").unwrap();
+
+        // When the journal is compacted
+        tracker.compact().unwrap();
+
+        // Then the snapshot holds every record and nothing else
+        let contents = std::fs::read_to_string(&journal).unwrap();
+        assert_eq!(contents.lines().count(), 3);
+        assert_eq!(tracker.records().unwrap().count(), 3);
+    }
+
+    #[test]
+    fn project_and_tags_survive_replay() {
+        // Given a session with a project and a tag
+        let (_temp, journal, lockfile) = temp_paths();
+        let mut tracker = new_journal_tracker(&journal, &lockfile);
+        let details = SessionDetails {
+            project: Some("foo".to_string()),
+            tags: ["review".to_string()].into(),
+            ..Default::default()
+        };
+        tracker.start_with(details.clone()).unwrap();
+
+        // When the session is stopped
+        tracker.stop().unwrap();
+
+        // Then the replayed record carries the details
+        let record = tracker.records().unwrap().next().unwrap();
+        assert_eq!(record.details, details);
+    }
+
+    #[test]
+    fn entries_without_id_get_a_stable_one() {
+        // Given a journal entry written before records had identifiers
+        let (_temp, journal, lockfile) = temp_paths();
+        std::fs::write(
+            &journal,
+            "{\"event\":\"record\",\"start\":\"2026-10-17T09:00:00Z\",\"end\":\"2026-10-17T10:00:00Z\"}\n",
+        )
+        .unwrap();
+        let tracker = new_journal_tracker(&journal, &lockfile);
+
+        // When reading it twice
+        let first = tracker.records().unwrap().next().unwrap();
+        let second = tracker.records().unwrap().next().unwrap();
+
+        // Then it has the same identifier both times
+        assert_eq!(first.id(), second.id());
+        assert_ne!(first.id(), RecordId::default());
+    }
+
+    #[test]
+    fn edits_and_deletions_are_replayed() {
+        // Given two recorded sessions
+        let (_temp, journal, lockfile) = temp_paths();
+        let mut tracker = new_journal_tracker(&journal, &lockfile);
+        for _ in 0..2 {
+            tracker.start().unwrap();
+            tracker.stop().unwrap();
+        }
+        let ids = tracker
+            .records()
+            .unwrap()
+            .map(|r| r.id())
+            .collect::<Vec<_>>();
+
+        // When editing the first and deleting the second
+        let edit = RecordEdit {
+            project: Some(Some("foo".to_string())),
+            ..Default::default()
+        };
+        tracker.edit_record(ids[0], edit).unwrap();
+        tracker.delete_record(ids[1]).unwrap();
+
+        // Then only the edited record is left, also after compaction
+        let records = tracker.records().unwrap().collect::<Vec<_>>();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].project(), Some("foo"));
+        tracker.compact().unwrap();
+        assert_eq!(tracker.records().unwrap().count(), 1);
+    }
+
+    #[test]
+    fn changes_are_applied_together() {
+        // Given two recorded sessions
+        let (_temp, journal, lockfile) = temp_paths();
+        let mut tracker = new_journal_tracker(&journal, &lockfile);
+        for _ in 0..2 {
+            tracker.start().unwrap();
+            tracker.stop().unwrap();
+        }
+        let ids = tracker
+            .records()
+            .unwrap()
+            .map(|r| r.id())
+            .collect::<Vec<_>>();
+
+        // When editing the first, deleting the second and adding one from
+        // yesterday at once
+        let edit = RecordEdit {
+            project: Some(Some("foo".to_string())),
+            ..Default::default()
+        };
+        let yesterday = Utc::now() - chrono::TimeDelta::days(1);
+        let added = TimeRecord::new(
+            StartTime(yesterday),
+            EndTime(yesterday + chrono::TimeDelta::hours(1)),
+            SessionDetails::default(),
+        );
+        tracker
+            .apply_changes(RecordChanges {
+                edits: vec![(ids[0], edit)],
+                deletions: vec![ids[1]],
+                additions: vec![added.clone()],
+            })
+            .unwrap();
+
+        // Then the added and the edited record are left
+        let records = tracker.records().unwrap().collect::<Vec<_>>();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().any(|record| record.id() == added.id()));
+        assert!(records.iter().any(|record| record.project() == Some("foo")));
+    }
+
+    #[test]
+    fn changes_overlapping_running_session_are_refused() {
+        // Given a session running for an hour
+        let (_temp, journal, lockfile) = temp_paths();
+        let mut tracker = new_journal_tracker(&journal, &lockfile);
+        let started = Utc::now() - chrono::TimeDelta::hours(1);
+        tracker
+            .start_at(StartTime(started), SessionDetails::default())
+            .unwrap();
+
+        // When adding a record from half an hour ago
+        let start = Utc::now() - chrono::TimeDelta::minutes(30);
+        let result = tracker.apply_changes(RecordChanges {
+            additions: vec![TimeRecord::new(
+                StartTime(start),
+                EndTime(start + chrono::TimeDelta::minutes(10)),
+                SessionDetails::default(),
+            )],
+            ..Default::default()
+        });
+
+        // Then it is refused
+        assert!(result.is_err());
+        assert_eq!(tracker.records().unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,122 @@
+//! Advisory file locks shared between tracker processes
+
+use std::{
+    fs::{File, OpenOptions, TryLockError},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use error_stack::{Result, ResultExt};
+
+/// How often a contended lock is retried
+const RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, thiserror::Error)]
+#[error("file lock error")]
+pub struct LockError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    /// Any number of readers may hold the lock
+    Shared,
+    /// A single writer holds the lock
+    Exclusive,
+}
+
+/// An OS-level advisory lock, released when dropped
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+}
+
+impl FileLock {
+    /// Locks `path`, creating it if needed, waiting at most `timeout`
+    pub fn acquire<P>(path: P, mode: LockMode, timeout: Duration) -> Result<Self, LockError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .change_context(LockError)
+            .attach_printable_lazy(|| format!("failed to open lock file '{}'", path.display()))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let attempt = match mode {
+                LockMode::Shared => file.try_lock_shared(),
+                LockMode::Exclusive => file.try_lock(),
+            };
+            match attempt {
+                Ok(()) => return Ok(Self { file }),
+                Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                    std::thread::sleep(RETRY_INTERVAL);
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(LockError).attach_printable_lazy(|| {
+                        format!(
+                            "timed out after {timeout:?} waiting for lock on '{}'",
+                            path.display()
+                        )
+                    });
+                }
+                Err(TryLockError::Error(e)) => {
+                    return Err(e)
+                        .change_context(LockError)
+                        .attach_printable_lazy(|| format!("failed to lock '{}'", path.display()));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        // closing the file releases the lock as well, this just makes it explicit
+        let _ = self.file.unlock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{prelude::PathChild, TempDir};
+
+    use super::*;
+
+    #[test]
+    fn shared_locks_do_not_block_each_other() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.child("db.lock");
+
+        let _first = FileLock::acquire(&path, LockMode::Shared, Duration::ZERO).unwrap();
+        let second = FileLock::acquire(&path, LockMode::Shared, Duration::ZERO);
+
+        assert!(second.is_ok());
+    }
+
+    #[test]
+    fn exclusive_lock_times_out_while_held() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.child("db.lock");
+
+        let _held = FileLock::acquire(&path, LockMode::Shared, Duration::ZERO).unwrap();
+        let exclusive = FileLock::acquire(&path, LockMode::Exclusive, Duration::from_millis(20));
+
+        assert!(exclusive.is_err());
+    }
+
+    #[test]
+    fn lock_is_released_on_drop() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.child("db.lock");
+
+        drop(FileLock::acquire(&path, LockMode::Exclusive, Duration::ZERO).unwrap());
+        let again = FileLock::acquire(&path, LockMode::Exclusive, Duration::ZERO);
+
+        assert!(again.is_ok());
+    }
+}
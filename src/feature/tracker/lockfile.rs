@@ -0,0 +1,374 @@
+//! The lockfile marking a running session
+//!
+//! The lockfile only exists while the tracker is running and holds the
+//! details of the current session.
+
+use std::{fs::OpenOptions, io::Write, path::Path, time::Duration};
+
+use error_stack::{Result, ResultExt};
+use serde::{Deserialize, Serialize};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    error::Suggestion,
+    feature::tracker::{
+        atomic, Break, EndTime, PauseStatus, RecordId, ResumeStatus, SessionDetails, StartTime,
+        StartupStatus, TimeRecord, TrackerState,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+#[error("lockfile error")]
+pub struct LockfileError;
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct LockfileData {
+    pub start_time: StartTime,
+    #[serde(flatten)]
+    pub details: SessionDetails,
+    /// Breaks taken so far
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breaks: Vec<Break>,
+    /// Start of the current break, if paused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paused_at: Option<DateTime<Utc>>,
+}
+
+impl LockfileData {
+    pub fn new(start_time: StartTime, details: SessionDetails) -> Self {
+        Self {
+            start_time,
+            details,
+            breaks: Vec::default(),
+            paused_at: None,
+        }
+    }
+
+    pub fn start(&self) -> DateTime<Utc> {
+        self.start_time.0
+    }
+
+    /// Time tracked so far, not counting breaks
+    pub fn elapsed(&self, now: DateTime<Utc>) -> Duration {
+        self.snapshot(now).duration()
+    }
+
+    /// The session up to `now` as a record
+    ///
+    /// The record has no identifier, as it isn't recorded yet.
+    pub fn snapshot(&self, now: DateTime<Utc>) -> TimeRecord {
+        let mut record = self.clone().finish(EndTime(now));
+        record.id = RecordId::default();
+        record
+    }
+
+    pub fn state(&self) -> TrackerState {
+        if self.paused_at.is_some() {
+            TrackerState::Paused
+        } else {
+            TrackerState::Running
+        }
+    }
+
+    pub fn pause(&mut self, at: DateTime<Utc>) -> PauseStatus {
+        if self.paused_at.is_some() {
+            PauseStatus::AlreadyPaused
+        } else {
+            self.paused_at = Some(at);
+            PauseStatus::Paused
+        }
+    }
+
+    pub fn resume(&mut self, at: DateTime<Utc>) -> ResumeStatus {
+        match self.paused_at.take() {
+            Some(start) => {
+                self.breaks.push(Break { start, end: at });
+                ResumeStatus::Resumed
+            }
+            None => ResumeStatus::NotPaused,
+        }
+    }
+
+    /// Ends the session at `end`, refusing to end before it started
+    pub fn finish_at(self, end: EndTime) -> Result<TimeRecord, LockfileError> {
+        if end.0 < self.start_time.0 {
+            return Err(LockfileError)
+                .attach_printable(format!(
+                    "stop time {} is before the session started at {}",
+                    end.0, self.start_time.0
+                ))
+                .attach(Suggestion("pick a stop time after the session started"));
+        }
+        Ok(self.finish(end))
+    }
+
+    /// Ends the session, closing the current break if paused
+    ///
+    /// Breaks are cut to the session, so that a stop backdated into or
+    /// before a break only leaves out the time actually paused.
+    pub fn finish(mut self, end: EndTime) -> TimeRecord {
+        self.resume(end.0);
+        let (from, to) = (self.start_time.0, end.0);
+        TimeRecord {
+            id: RecordId::generate(&self.start_time),
+            start: self.start_time,
+            end,
+            details: self.details,
+            breaks: self.breaks,
+        }
+        .clipped(from, to)
+    }
+}
+
+/// Creates the lockfile, unless a session is already running
+pub fn create<P>(lockfile: P, data: &LockfileData) -> Result<StartupStatus, LockfileError>
+where
+    P: AsRef<Path>,
+{
+    let lockfile_data = serde_json::to_string(data)
+        .change_context(LockfileError)
+        .attach_printable("failed to serialize lockfile data")?;
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(lockfile.as_ref());
+
+    if let Ok(mut file) = file {
+        file.write_all(lockfile_data.as_bytes())
+            .change_context(LockfileError)
+            .attach_printable("failed to write lockfile data")?;
+        Ok(StartupStatus::Started)
+    } else {
+        Ok(StartupStatus::Running)
+    }
+}
+
+pub fn read<P>(lockfile: P) -> Result<LockfileData, LockfileError>
+where
+    P: AsRef<Path>,
+{
+    let file = OpenOptions::new()
+        .read(true)
+        .open(lockfile.as_ref())
+        .change_context(LockfileError)
+        .attach_printable("failed to open lockfile")?;
+
+    serde_json::from_reader(file)
+        .change_context(LockfileError)
+        .attach_printable("failed to deserialize lockfile")
+}
+
+/// Replaces the data of an existing lockfile
+pub fn write<P>(lockfile: P, data: &LockfileData) -> Result<(), LockfileError>
+where
+    P: AsRef<Path>,
+{
+    let lockfile_data = serde_json::to_string(data)
+        .change_context(LockfileError)
+        .attach_printable("failed to serialize lockfile data")?;
+
+    atomic::replace(lockfile.as_ref(), lockfile_data.as_bytes())
+        .change_context(LockfileError)
+        .attach_printable("failed to write lockfile data")
+}
+
+/// Applies `change` to the running session
+pub fn update<P, F, T>(lockfile: P, change: F) -> Result<T, LockfileError>
+where
+    P: AsRef<Path>,
+    F: FnOnce(&mut LockfileData) -> T,
+{
+    let lockfile = lockfile.as_ref();
+    if !lockfile.exists() {
+        return Err(LockfileError)
+            .attach_printable("the tracker is not running")
+            .attach(Suggestion("start tracking first with `track start`"));
+    }
+
+    let mut data = read(lockfile)?;
+    let result = change(&mut data);
+    write(lockfile, &data)?;
+    Ok(result)
+}
+
+pub fn state<P>(lockfile: P) -> Result<TrackerState, LockfileError>
+where
+    P: AsRef<Path>,
+{
+    let lockfile = lockfile.as_ref();
+    if lockfile.exists() {
+        Ok(read(lockfile)?.state())
+    } else {
+        Ok(TrackerState::Stopped)
+    }
+}
+
+/// The running session, `None` if the lockfile doesn't exist
+pub fn session<P>(lockfile: P) -> Result<Option<LockfileData>, LockfileError>
+where
+    P: AsRef<Path>,
+{
+    let lockfile = lockfile.as_ref();
+    if lockfile.exists() {
+        read(lockfile).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+pub fn remove<P>(lockfile: P) -> Result<(), LockfileError>
+where
+    P: AsRef<Path>,
+{
+    std::fs::remove_file(lockfile.as_ref())
+        .change_context(LockfileError)
+        .attach_printable("unable to delete lockfile")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    fn session_started_at(start: DateTime<Utc>) -> LockfileData {
+        LockfileData::new(StartTime(start), SessionDetails::default())
+    }
+
+    #[test]
+    fn pausing_twice_reports_already_paused() {
+        let now = Utc::now();
+        let mut session = session_started_at(now);
+
+        session.pause(now);
+        let status = session.pause(now);
+
+        assert_eq!(status, PauseStatus::AlreadyPaused);
+        assert_eq!(session.state(), TrackerState::Paused);
+    }
+
+    #[test]
+    fn resuming_without_pause_reports_not_paused() {
+        let now = Utc::now();
+        let mut session = session_started_at(now);
+
+        let status = session.resume(now);
+
+        assert_eq!(status, ResumeStatus::NotPaused);
+        assert_eq!(session.state(), TrackerState::Running);
+    }
+
+    #[test]
+    fn breaks_are_excluded_from_record_duration() {
+        // Given a one hour session with a 10 minute break
+        let start = Utc::now();
+        let mut session = session_started_at(start);
+        session.pause(start + TimeDelta::minutes(20));
+        session.resume(start + TimeDelta::minutes(30));
+
+        // When the session is finished
+        let record = session.finish(EndTime(start + TimeDelta::hours(1)));
+
+        // Then the record holds 50 minutes of work
+        assert_eq!(record.duration(), Duration::from_secs(50 * 60));
+    }
+
+    #[test]
+    fn finishing_while_paused_closes_the_break() {
+        // Given a one hour session paused after 45 minutes
+        let start = Utc::now();
+        let mut session = session_started_at(start);
+        session.pause(start + TimeDelta::minutes(45));
+
+        // When the session is finished
+        let record = session.finish(EndTime(start + TimeDelta::hours(1)));
+
+        // Then the record holds 45 minutes of work
+        assert_eq!(record.breaks().len(), 1);
+        assert_eq!(record.duration(), Duration::from_secs(45 * 60));
+    }
+
+    #[test]
+    fn finishing_before_the_start_is_refused() {
+        // Given a running session
+        let start = Utc::now();
+        let session = session_started_at(start);
+
+        // When finishing it before it started
+        let result = session.finish_at(EndTime(start - TimeDelta::minutes(1)));
+
+        // Then nothing is recorded
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn elapsed_time_excludes_the_current_break() {
+        // Given a session paused after 45 minutes
+        let start = Utc::now();
+        let mut session = session_started_at(start);
+        session.pause(start + TimeDelta::minutes(45));
+
+        // When asked an hour after the start
+        let elapsed = session.elapsed(start + TimeDelta::hours(1));
+
+        // Then only the time before the break counts
+        assert_eq!(elapsed, Duration::from_secs(45 * 60));
+        assert_eq!(session.state(), TrackerState::Paused);
+    }
+
+    #[test]
+    fn backdated_stop_cuts_later_breaks() {
+        // Given a session from 09:00 with a break from 10:00 to 10:30
+        let start = Utc::now();
+        let mut session = session_started_at(start);
+        session.pause(start + TimeDelta::minutes(60));
+        session.resume(start + TimeDelta::minutes(90));
+
+        // When it is stopped at 09:45
+        let record = session
+            .finish_at(EndTime(start + TimeDelta::minutes(45)))
+            .unwrap();
+
+        // Then all 45 minutes count
+        assert!(record.breaks().is_empty());
+        assert_eq!(record.duration(), Duration::from_secs(45 * 60));
+    }
+
+    #[test]
+    fn stop_backdated_before_pause_leaves_no_inverted_break() {
+        // Given a session paused 30 minutes after it started
+        let start = Utc::now();
+        let mut session = session_started_at(start);
+        session.pause(start + TimeDelta::minutes(30));
+
+        // When it is stopped 20 minutes after it started
+        let record = session
+            .finish_at(EndTime(start + TimeDelta::minutes(20)))
+            .unwrap();
+
+        // Then there is no break and all 20 minutes count
+        assert!(record.breaks().is_empty());
+        assert_eq!(record.duration(), Duration::from_secs(20 * 60));
+    }
+
+    #[test]
+    fn stop_backdated_into_break_cuts_it() {
+        // Given a session paused 30 minutes after it started
+        let start = Utc::now();
+        let mut session = session_started_at(start);
+        session.pause(start + TimeDelta::minutes(30));
+
+        // When it is stopped 40 minutes after it started
+        let record = session
+            .finish_at(EndTime(start + TimeDelta::minutes(40)))
+            .unwrap();
+
+        // Then only the 10 paused minutes before the stop are left out
+        assert_eq!(record.duration(), Duration::from_secs(30 * 60));
+        assert_eq!(record.breaks()[0].duration(), Duration::from_secs(10 * 60));
+    }
+}
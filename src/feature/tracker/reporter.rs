@@ -1,13 +1,285 @@
-use std::time::Duration;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    time::Duration,
+};
 
-use chrono::Utc;
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc, Weekday};
 use error_stack::{Result, ResultExt};
 
-use crate::feature::tracker::Tracker;
+use crate::{
+    common::time::Zone,
+    feature::tracker::{TimeRecord, Tracker},
+};
 
 #[derive(Debug, Clone, Copy)]
 pub enum ReportTimespan {
     Last(Duration),
+    Today,
+    Yesterday,
+    ThisWeek,
+    LastWeek,
+    ThisMonth,
+    /// From `from` up to, but not including, `to`
+    Range {
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    },
+}
+
+impl ReportTimespan {
+    /// Start and exclusive end of the timespan
+    ///
+    /// Calendar days begin at midnight in `zone`, weeks on `week_start`.
+    pub fn bounds(
+        self,
+        now: DateTime<Utc>,
+        zone: Zone,
+        week_start: Weekday,
+    ) -> (DateTime<Utc>, DateTime<Utc>) {
+        let today = zone.date_of(now);
+        let this_week = today.week(week_start).first_day();
+        let this_month = today.with_day(1).unwrap_or(today);
+        let (from, to) = match self {
+            Self::Last(duration) => return (now - duration, now),
+            Self::Range { from, to } => return (from, to),
+            Self::Today => (today, today + Days::new(1)),
+            Self::Yesterday => (today - Days::new(1), today),
+            Self::ThisWeek => (this_week, this_week + Days::new(7)),
+            Self::LastWeek => (this_week - Days::new(7), this_week),
+            Self::ThisMonth => (this_month, this_month + Months::new(1)),
+        };
+        (zone.start_of_day(from), zone.start_of_day(to))
+    }
+}
+
+/// Selects the records a report covers
+#[derive(Debug, Clone)]
+pub struct ReportQuery {
+    timespan: ReportTimespan,
+    zone: Zone,
+    week_start: Weekday,
+    include_running: bool,
+    project: Option<String>,
+    tags: BTreeSet<String>,
+}
+
+impl ReportQuery {
+    pub fn new(timespan: ReportTimespan) -> Self {
+        Self {
+            timespan,
+            zone: Zone::default(),
+            week_start: Weekday::Mon,
+            include_running: false,
+            project: None,
+            tags: BTreeSet::default(),
+        }
+    }
+
+    /// Sets the timezone calendar days are counted in
+    pub fn with_zone(mut self, zone: Zone) -> Self {
+        self.zone = zone;
+        self
+    }
+
+    /// Sets the first day of the week, Monday by default
+    pub fn with_week_start(mut self, week_start: Weekday) -> Self {
+        self.week_start = week_start;
+        self
+    }
+
+    /// Also count the running session up to now
+    pub fn with_running(mut self, include: bool) -> Self {
+        self.include_running = include;
+        self
+    }
+
+    /// Start and exclusive end of the timespan as of `now`
+    pub fn bounds(&self, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        self.timespan.bounds(now, self.zone, self.week_start)
+    }
+
+    /// Only include records of `project`
+    pub fn with_project<P>(mut self, project: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.project = Some(project.into());
+        self
+    }
+
+    /// Only include records tagged with `tag`; every added tag is required
+    pub fn with_tag<T>(mut self, tag: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.tags.insert(tag.into());
+        self
+    }
+
+    fn matches(&self, record: &TimeRecord) -> bool {
+        let project_matches = match &self.project {
+            Some(project) => record.project() == Some(project.as_str()),
+            None => true,
+        };
+        project_matches && self.tags.is_subset(record.tags())
+    }
+}
+
+/// How durations are grouped in a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GroupBy {
+    /// Calendar days; records running across midnight are split
+    Day,
+    /// Calendar weeks, beginning on the query's week start
+    Week,
+    Month,
+    Project,
+    /// Records with several tags count towards each of them
+    Tag,
+}
+
+impl GroupBy {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::Project => "project",
+            Self::Tag => "tag",
+        }
+    }
+
+    /// The groups `record` counts towards, with the part counting to each
+    ///
+    /// Records without a project, or without any tag, are grouped under
+    /// [`GroupKey::Name`] of `None`.
+    fn split(self, record: TimeRecord, query: &ReportQuery) -> Vec<(GroupKey, TimeRecord)> {
+        match self {
+            Self::Project => vec![(GroupKey::Name(record.project().map(str::to_string)), record)],
+            Self::Tag if record.tags().is_empty() => vec![(GroupKey::Name(None), record)],
+            Self::Tag => record
+                .tags()
+                .iter()
+                .map(|tag| (GroupKey::Name(Some(tag.clone())), record.clone()))
+                .collect(),
+            Self::Day | Self::Week | Self::Month => self.split_by_period(record, query),
+        }
+    }
+
+    fn split_by_period(
+        self,
+        record: TimeRecord,
+        query: &ReportQuery,
+    ) -> Vec<(GroupKey, TimeRecord)> {
+        let first = query.zone.date_of(record.start());
+        let mut period = match self {
+            Self::Week => first.week(query.week_start).first_day(),
+            Self::Month => first.with_day(1).unwrap_or(first),
+            _ => first,
+        };
+
+        let mut parts = Vec::new();
+        loop {
+            let next = match self {
+                Self::Week => period + Days::new(7),
+                Self::Month => period + Months::new(1),
+                _ => period + Days::new(1),
+            };
+            let (from, to) = (
+                query.zone.start_of_day(period),
+                query.zone.start_of_day(next),
+            );
+            parts.push((
+                GroupKey::Period(self, period),
+                record.clone().clipped(from, to),
+            ));
+            if to >= record.end() {
+                return parts;
+            }
+            period = next;
+        }
+    }
+}
+
+/// A group in a [`Breakdown`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GroupKey {
+    /// A day, week or month, by its first day
+    Period(GroupBy, NaiveDate),
+    /// A project or tag, `None` for records without any
+    Name(Option<String>),
+}
+
+impl fmt::Display for GroupKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Period(GroupBy::Week, date) => write!(f, "week of {}", date.format("%Y-%m-%d")),
+            Self::Period(GroupBy::Month, date) => write!(f, "{}", date.format("%Y-%m")),
+            Self::Period(_, date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            Self::Name(Some(name)) => f.write_str(name),
+            Self::Name(None) => f.write_str("(none)"),
+        }
+    }
+}
+
+/// Everything a report shows
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub from: DateTime<Utc>,
+    /// End of the timespan, exclusive
+    pub to: DateTime<Utc>,
+    pub breakdown: Breakdown,
+    /// The records covered, clipped to the timespan
+    pub records: Vec<TimeRecord>,
+}
+
+/// Durations of a report, nested by one grouping after another
+#[derive(Debug, Clone, Default)]
+pub struct Breakdown {
+    /// Time spent in this group, including each of its subgroups once
+    pub total: Duration,
+    /// Whether the running session counts towards this group
+    pub running: bool,
+    /// Billable time, once a [`Rounding`](crate::feature::rounding::Rounding) is applied
+    pub rounded: Option<Duration>,
+    /// The records counted in this group, split at period boundaries
+    pub records: Vec<TimeRecord>,
+    pub groups: BTreeMap<GroupKey, Breakdown>,
+}
+
+impl Breakdown {
+    fn of(records: Vec<TimeRecord>, groupings: &[GroupBy], query: &ReportQuery) -> Self {
+        let total = records.iter().map(TimeRecord::duration).sum();
+        let running = records.iter().any(TimeRecord::is_running);
+        let Some((grouping, rest)) = groupings.split_first() else {
+            return Self {
+                total,
+                running,
+                rounded: None,
+                records,
+                groups: BTreeMap::default(),
+            };
+        };
+
+        let mut parts = BTreeMap::<GroupKey, Vec<TimeRecord>>::new();
+        for record in records.iter().cloned() {
+            for (key, part) in grouping.split(record, query) {
+                parts.entry(key).or_default().push(part);
+            }
+        }
+        let groups = parts
+            .into_iter()
+            .map(|(key, records)| (key, Self::of(records, rest, query)))
+            .collect();
+        Self {
+            total,
+            running,
+            rounded: None,
+            records,
+            groups,
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -15,27 +287,77 @@ pub enum ReportTimespan {
 pub struct ReporterError;
 
 pub trait Reporter: Tracker {
-    fn total_duration(&self, timespan: ReportTimespan) -> Result<Duration, ReporterError> {
-        match timespan {
-            ReportTimespan::Last(duration) => {
-                let earliest_time_to_consider = (Utc::now() - duration).timestamp_millis();
-                let total_ms = self
-                    .records()
-                    .change_context(ReporterError)
-                    .attach_printable("failed to get records")?
-                    .filter_map(|rec| {
-                        if rec.start.timestamp_millis() >= earliest_time_to_consider {
-                            let time_delta =
-                                rec.end.timestamp_millis() - rec.start.timestamp_millis();
-                            Some(time_delta)
-                        } else {
-                            None
-                        }
-                    })
-                    .sum::<i64>();
-                Ok(Duration::from_millis(total_ms as u64))
-            }
+    /// Records covered by `query`, clipped to its timespan
+    ///
+    /// A record running across the start or end of the timespan only counts
+    /// with the time inside of it. The running session comes last, if the
+    /// query includes it.
+    fn matching_records(&self, query: &ReportQuery) -> Result<Vec<TimeRecord>, ReporterError> {
+        let (from, to) = query
+            .timespan
+            .bounds(Utc::now(), query.zone, query.week_start);
+        let mut records: Vec<_> = self
+            .records_overlapping(from, to)
+            .change_context(ReporterError)
+            .attach_printable("failed to get records")?
+            .filter(|rec| query.matches(rec))
+            .map(|rec| rec.clipped(from, to))
+            .collect();
+        records.extend(self.running_record(query)?);
+        Ok(records)
+    }
+
+    /// The running session up to now, clipped to the timespan of `query`
+    ///
+    /// `None` unless the query includes the running session and it matches.
+    fn running_record(&self, query: &ReportQuery) -> Result<Option<TimeRecord>, ReporterError> {
+        if !query.include_running {
+            return Ok(None);
         }
+        let session = self
+            .current_session()
+            .change_context(ReporterError)
+            .attach_printable("failed to get running session")?;
+
+        let now = Utc::now();
+        let (from, to) = query.timespan.bounds(now, query.zone, query.week_start);
+        Ok(session
+            .map(|session| session.snapshot(now))
+            .filter(|rec| rec.start() < to && rec.end() > from && query.matches(rec))
+            .map(|rec| rec.clipped(from, to)))
+    }
+
+    fn total_duration(&self, query: &ReportQuery) -> Result<Duration, ReporterError> {
+        Ok(self
+            .matching_records(query)?
+            .iter()
+            .map(TimeRecord::duration)
+            .sum())
+    }
+
+    /// Durations grouped by each of `groupings` in turn
+    ///
+    /// E.g. grouping by day and then project gives the time spent on each
+    /// project within each day.
+    fn breakdown(
+        &self,
+        query: &ReportQuery,
+        groupings: &[GroupBy],
+    ) -> Result<Breakdown, ReporterError> {
+        let records = self.matching_records(query)?;
+        Ok(Breakdown::of(records, groupings, query))
+    }
+
+    /// The timespan, durations and records of a report
+    fn report(&self, query: &ReportQuery, groupings: &[GroupBy]) -> Result<Report, ReporterError> {
+        let (from, to) = query.bounds(Utc::now());
+        let records = self.matching_records(query)?;
+        Ok(Report {
+            from,
+            to,
+            breakdown: Breakdown::of(records.clone(), groupings, query),
+            records,
+        })
     }
 }
 
@@ -43,7 +365,9 @@ pub trait Reporter: Tracker {
 mod tests {
     use std::time::Duration;
 
-    use crate::feature::tracker::tlib::FakeTracker;
+    use crate::feature::tracker::{
+        tlib::FakeTracker, Break, EndTime, Overlap, SessionDetails, StartTime,
+    };
 
     use super::*;
 
@@ -56,7 +380,9 @@ mod tests {
 
         // When calculating duration
         let duration = tracker
-            .total_duration(ReportTimespan::Last(Duration::from_secs(1)))
+            .total_duration(&ReportQuery::new(ReportTimespan::Last(
+                Duration::from_secs(1),
+            )))
             .unwrap();
 
         // Then there is no duration due to no records
@@ -76,9 +402,306 @@ mod tests {
 
         // When the duration is calculated
         let duration = tracker
-            .total_duration(ReportTimespan::Last(Duration::from_secs(1)))
+            .total_duration(&ReportQuery::new(ReportTimespan::Last(
+                Duration::from_secs(1),
+            )))
             .unwrap();
         // Then duration is at least 20ms
         assert!(duration >= Duration::from_millis(20));
     }
+
+    fn tracker_with_sessions(sessions: &[(Option<&str>, &[&str])]) -> FakeTracker {
+        let mut tracker = FakeTracker::default();
+        for (project, tags) in sessions {
+            let details = SessionDetails {
+                project: project.map(str::to_string),
+                tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                ..Default::default()
+            };
+            tracker.start_with(details).unwrap();
+            std::thread::sleep(Duration::from_millis(10));
+            tracker.stop().unwrap();
+        }
+        tracker
+    }
+
+    #[test]
+    fn filters_by_project_and_tag() {
+        // Given records of two projects, one of them tagged
+        let tracker = tracker_with_sessions(&[
+            (Some("foo"), &["review"]),
+            (Some("foo"), &[]),
+            (Some("bar"), &["review"]),
+        ]);
+
+        // When records of one project with one tag are requested
+        let query = ReportQuery::new(ReportTimespan::Last(Duration::from_secs(1)))
+            .with_project("foo")
+            .with_tag("review");
+        let records = tracker.matching_records(&query).unwrap();
+
+        // Then only the matching record is returned
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].project(), Some("foo"));
+    }
+
+    #[test]
+    fn groups_durations_by_tag() {
+        // Given records with overlapping tags
+        let tracker = tracker_with_sessions(&[(None, &["a", "b"]), (None, &["b"]), (None, &[])]);
+
+        // When durations are grouped by tag
+        let query = ReportQuery::new(ReportTimespan::Last(Duration::from_secs(1)));
+        let breakdown = tracker.breakdown(&query, &[GroupBy::Tag]).unwrap();
+
+        // Then each tag and the untagged record have their own group
+        let groups = breakdown.groups;
+        let keys = groups.keys().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(keys, vec!["(none)", "a", "b"]);
+        let tag = |name: &str| GroupKey::Name(Some(name.to_string()));
+        assert!(groups[&tag("b")].total > groups[&tag("a")].total);
+    }
+
+    #[test]
+    fn paused_time_is_excluded_from_total() {
+        // Given a session that spent most of its time paused
+        let mut tracker = FakeTracker::default();
+        tracker.start().unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+        tracker.pause().unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        tracker.resume().unwrap();
+        tracker.stop().unwrap();
+
+        // When the duration is calculated
+        let duration = tracker
+            .total_duration(&ReportQuery::new(ReportTimespan::Last(
+                Duration::from_secs(1),
+            )))
+            .unwrap();
+
+        // Then the break is not counted
+        assert!(duration >= Duration::from_millis(10));
+        assert!(duration < Duration::from_millis(100));
+    }
+
+    fn berlin() -> Zone {
+        Zone::Named(chrono_tz::Europe::Berlin)
+    }
+
+    fn berlin_time(input: &str) -> DateTime<Utc> {
+        crate::common::time::parse_time(input, Utc::now(), berlin()).unwrap()
+    }
+
+    #[test]
+    fn today_spans_the_local_calendar_day() {
+        // Given a time shortly after midnight in Berlin
+        let now = berlin_time("2026-10-17 00:30");
+
+        // When the bounds of today are computed
+        let (from, to) = ReportTimespan::Today.bounds(now, berlin(), Weekday::Mon);
+
+        // Then they are the local midnights around it
+        assert_eq!(from, berlin_time("2026-10-17 00:00"));
+        assert_eq!(to, berlin_time("2026-10-18 00:00"));
+    }
+
+    #[test]
+    fn weeks_begin_on_the_configured_day() {
+        // Given a Saturday
+        let now = berlin_time("2026-10-17 12:00");
+
+        // When the weeks start on Monday or on Sunday
+        let monday = ReportTimespan::ThisWeek.bounds(now, berlin(), Weekday::Mon);
+        let sunday = ReportTimespan::LastWeek.bounds(now, berlin(), Weekday::Sun);
+
+        // Then the week around it starts accordingly
+        assert_eq!(monday.0, berlin_time("2026-10-12 00:00"));
+        assert_eq!(monday.1, berlin_time("2026-10-19 00:00"));
+        assert_eq!(sunday.0, berlin_time("2026-10-04 00:00"));
+        assert_eq!(sunday.1, berlin_time("2026-10-11 00:00"));
+    }
+
+    #[test]
+    fn month_spanning_a_clock_change_keeps_local_midnights() {
+        // Given a day in the month the clocks go back
+        let now = berlin_time("2026-10-17 12:00");
+
+        // When the bounds of the month are computed
+        let (from, to) = ReportTimespan::ThisMonth.bounds(now, berlin(), Weekday::Mon);
+
+        // Then they are local midnights, an hour longer than 31 days apart
+        assert_eq!(from, berlin_time("2026-10-01 00:00"));
+        assert_eq!(to, berlin_time("2026-11-01 00:00"));
+        assert_eq!(
+            to - from,
+            chrono::TimeDelta::days(31) + chrono::TimeDelta::hours(1)
+        );
+    }
+
+    fn tracker_with_record(start: &str, end: &str, breaks: &[(&str, &str)]) -> FakeTracker {
+        let mut record = TimeRecord::new(
+            StartTime::at(berlin_time(start)),
+            EndTime::at(berlin_time(end)),
+            SessionDetails::default(),
+        );
+        record.breaks = breaks
+            .iter()
+            .map(|(start, end)| Break {
+                start: berlin_time(start),
+                end: berlin_time(end),
+            })
+            .collect();
+        let mut tracker = FakeTracker::default();
+        tracker.add_record(record, Overlap::Allow).unwrap();
+        tracker
+    }
+
+    fn range(from: &str, to: &str) -> ReportQuery {
+        let timespan = ReportTimespan::Range {
+            from: berlin_time(from),
+            to: berlin_time(to),
+        };
+        ReportQuery::new(timespan).with_zone(berlin())
+    }
+
+    #[test]
+    fn overnight_session_is_split_at_midnight() {
+        // Given a session from 22:00 to 02:00
+        let tracker = tracker_with_record("2026-10-16 22:00", "2026-10-17 02:00", &[]);
+
+        // When each of the two days is reported on
+        let first = tracker
+            .total_duration(&range("2026-10-16", "2026-10-17"))
+            .unwrap();
+        let second = tracker
+            .total_duration(&range("2026-10-17", "2026-10-18"))
+            .unwrap();
+
+        // Then each day counts its own two hours
+        assert_eq!(first, Duration::from_secs(2 * 3600));
+        assert_eq!(second, Duration::from_secs(2 * 3600));
+    }
+
+    #[test]
+    fn session_spanning_the_whole_window_counts_the_window() {
+        // Given a session from 08:00 to 18:00
+        let tracker = tracker_with_record("2026-10-17 08:00", "2026-10-17 18:00", &[]);
+
+        // When an hour in the middle of it is reported on
+        let duration = tracker
+            .total_duration(&range("2026-10-17 12:00", "2026-10-17 13:00"))
+            .unwrap();
+
+        // Then only that hour counts
+        assert_eq!(duration, Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn only_breaks_inside_the_window_are_subtracted() {
+        // Given a session with a break before the window and one across its end
+        let tracker = tracker_with_record(
+            "2026-10-17 08:00",
+            "2026-10-17 14:00",
+            &[
+                ("2026-10-17 09:00", "2026-10-17 10:00"),
+                ("2026-10-17 11:30", "2026-10-17 12:30"),
+            ],
+        );
+
+        // When the time from 10:00 to 12:00 is reported on
+        let query = range("2026-10-17 10:00", "2026-10-17 12:00");
+        let duration = tracker.total_duration(&query).unwrap();
+
+        // Then half an hour of the second break is subtracted
+        assert_eq!(duration, Duration::from_secs(90 * 60));
+    }
+
+    #[test]
+    fn sessions_outside_the_window_are_left_out() {
+        // Given a session ending right when the window starts
+        let tracker = tracker_with_record("2026-10-17 08:00", "2026-10-17 10:00", &[]);
+
+        // When the window is reported on
+        let records = tracker
+            .matching_records(&range("2026-10-17 10:00", "2026-10-17 12:00"))
+            .unwrap();
+
+        // Then the session is not part of it
+        assert!(records.is_empty());
+    }
+
+    #[test]
+    fn running_session_counts_when_included() {
+        // Given a session running for about an hour after a recorded one
+        let mut tracker = tracker_with_sessions(&[(Some("foo"), &[])]);
+        let start = Utc::now() - chrono::TimeDelta::hours(1);
+        tracker
+            .start_at(StartTime::at(start), SessionDetails::default())
+            .unwrap();
+
+        // When reports with and without the running session are made
+        let query = ReportQuery::new(ReportTimespan::Last(Duration::from_secs(2 * 3600)));
+        let without = tracker.total_duration(&query).unwrap();
+        let with = tracker
+            .total_duration(&query.clone().with_running(true))
+            .unwrap();
+
+        // Then only the latter counts the running hour, as its last record
+        assert!(without < Duration::from_secs(60));
+        assert!(with >= Duration::from_secs(3600));
+        let records = tracker.matching_records(&query.with_running(true)).unwrap();
+        assert!(records.last().unwrap().is_running());
+    }
+
+    #[test]
+    fn breakdown_splits_days_and_nests_projects() {
+        // Given an overnight session of one project and a session of another
+        let mut tracker = tracker_with_record("2026-10-16 22:00", "2026-10-17 02:00", &[]);
+        let details = SessionDetails {
+            project: Some("acme".to_string()),
+            ..Default::default()
+        };
+        let record = TimeRecord::new(
+            StartTime::at(berlin_time("2026-10-17 09:00")),
+            EndTime::at(berlin_time("2026-10-17 10:00")),
+            details,
+        );
+        tracker.add_record(record, Overlap::Allow).unwrap();
+
+        // When the records are broken down by day, then project
+        let query = range("2026-10-16", "2026-10-18");
+        let breakdown = tracker
+            .breakdown(&query, &[GroupBy::Day, GroupBy::Project])
+            .unwrap();
+
+        // Then each day holds its part of the overnight session
+        let hours = |hours: u64| Duration::from_secs(hours * 3600);
+        assert_eq!(breakdown.total, hours(5));
+        let days: Vec<_> = breakdown.groups.iter().collect();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].0.to_string(), "2026-10-16");
+        assert_eq!(days[0].1.total, hours(2));
+        assert_eq!(days[1].1.total, hours(3));
+        let projects = &days[1].1.groups;
+        assert_eq!(projects[&GroupKey::Name(None)].total, hours(2));
+        assert_eq!(
+            projects[&GroupKey::Name(Some("acme".to_string()))].total,
+            hours(1)
+        );
+    }
+
+    #[test]
+    fn breakdown_by_week_starts_on_the_week_start() {
+        // Given a session on a Sunday
+        let tracker = tracker_with_record("2026-10-18 09:00", "2026-10-18 10:00", &[]);
+
+        // When broken down by weeks beginning on Sunday
+        let query = range("2026-10-01", "2026-11-01").with_week_start(Weekday::Sun);
+        let breakdown = tracker.breakdown(&query, &[GroupBy::Week]).unwrap();
+
+        // Then it falls into the week beginning that day
+        let weeks: Vec<_> = breakdown.groups.keys().map(ToString::to_string).collect();
+        assert_eq!(weeks, vec!["week of 2026-10-18"]);
+    }
 }
@@ -0,0 +1,1140 @@
+//! A SQLite tracker
+
+// sqlite tracker
+// - "running" table: at most one row, the session being tracked
+// - "records" table: finished sessions, indexed by start time
+
+use std::{path::PathBuf, time::Duration};
+
+use chrono::{DateTime, Utc};
+use error_stack::{Result, ResultExt};
+use rusqlite::{
+    params, types::Type, Connection, OptionalExtension, Params, Row, Transaction,
+    TransactionBehavior,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    error::Suggestion,
+    feature::tracker::{
+        check_new_record, lockfile::LockfileData, unknown_record, EndTime, Overlap, PauseStatus,
+        RecordChanges, RecordEdit, RecordId, Reporter, ResumeStatus, SessionDetails, StartTime,
+        StartupStatus, TimeRecord, Tracker, TrackerError, TrackerState, DEFAULT_LOCK_TIMEOUT,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+#[error("sqlite tracker error")]
+pub struct SqliteTrackerError;
+
+/// Schema changes, applied in order and tracked with `PRAGMA user_version`
+const MIGRATIONS: &[&str] = &[
+    "
+    CREATE TABLE records (
+        id INTEGER PRIMARY KEY,
+        start_ms INTEGER NOT NULL,
+        end_ms INTEGER NOT NULL
+    );
+    CREATE INDEX records_start_ms ON records (start_ms);
+    CREATE TABLE running (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        start_ms INTEGER NOT NULL
+    );
+",
+    "
+    ALTER TABLE records ADD COLUMN project TEXT;
+    ALTER TABLE records ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';
+    CREATE INDEX records_project ON records (project);
+    ALTER TABLE running ADD COLUMN project TEXT;
+    ALTER TABLE running ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';
+",
+    "
+    ALTER TABLE records ADD COLUMN description TEXT;
+    ALTER TABLE running ADD COLUMN description TEXT;
+",
+    "
+    ALTER TABLE records ADD COLUMN breaks TEXT NOT NULL DEFAULT '[]';
+    ALTER TABLE running ADD COLUMN breaks TEXT NOT NULL DEFAULT '[]';
+    ALTER TABLE running ADD COLUMN paused_at_ms INTEGER;
+",
+    // record identifiers, backfilled by `backfill_record_ids`
+    "
+    ALTER TABLE records ADD COLUMN ulid TEXT;
+    CREATE UNIQUE INDEX records_ulid ON records (ulid);
+",
+    // looking up records that overlap a timespan from their end
+    "
+    CREATE INDEX records_end_ms ON records (end_ms);
+",
+];
+
+/// Columns of the records table, in the order read by [`record_from_row`]
+const RECORD_COLUMNS: &str = "start_ms, end_ms, project, tags, description, breaks, ulid";
+
+/// Columns of the running table, in the order read by [`running_session`]
+const RUNNING_COLUMNS: &str = "start_ms, project, tags, description, breaks, paused_at_ms";
+
+pub struct SqliteTracker {
+    db: PathBuf,
+    lock_timeout: Duration,
+}
+
+impl SqliteTracker {
+    pub fn new<D>(db: D) -> Self
+    where
+        D: Into<PathBuf>,
+    {
+        let db = db.into();
+        Self {
+            db,
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+        }
+    }
+
+    /// Sets how long to wait for other processes to release the database
+    pub fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = timeout;
+        self
+    }
+
+    /// Opens the database, bringing its schema up to date
+    fn connect(&self) -> Result<Connection, SqliteTrackerError> {
+        let mut conn = Connection::open(&self.db)
+            .change_context(SqliteTrackerError)
+            .attach_printable("failed to open database")?;
+        conn.busy_timeout(self.lock_timeout)
+            .change_context(SqliteTrackerError)
+            .attach_printable("failed to set busy timeout")?;
+        migrate(&mut conn)?;
+        Ok(conn)
+    }
+
+    fn start_impl(
+        &self,
+        start: StartTime,
+        details: SessionDetails,
+    ) -> Result<StartupStatus, SqliteTrackerError> {
+        let conn = self.connect()?;
+        let session = LockfileData::new(start, details);
+        let inserted = conn
+            .execute(
+                &format!(
+                    "INSERT OR IGNORE INTO running (id, {RUNNING_COLUMNS})
+                     VALUES (0, ?1, ?2, ?3, ?4, ?5, ?6)"
+                ),
+                running_params(&session)?,
+            )
+            .change_context(SqliteTrackerError)
+            .attach_printable("failed to insert running session")?;
+
+        if inserted == 1 {
+            Ok(StartupStatus::Started)
+        } else {
+            Ok(StartupStatus::Running)
+        }
+    }
+
+    fn stop_impl(
+        &self,
+        end: EndTime,
+        description: Option<String>,
+    ) -> Result<(), SqliteTrackerError> {
+        let mut conn = self.connect()?;
+        let tx = begin(&mut conn)?;
+
+        let mut session = running_session(&tx)?
+            .ok_or(SqliteTrackerError)
+            .attach_printable("the tracker is not running")?;
+        if let Some(description) = description {
+            session.details.append_description(description);
+        }
+        let record = session.finish_at(end).change_context(SqliteTrackerError)?;
+        insert_record(&tx, &record)?;
+        tx.execute("DELETE FROM running", [])
+            .change_context(SqliteTrackerError)
+            .attach_printable("failed to delete running session")?;
+
+        commit(tx)
+    }
+
+    fn cancel_impl(&self) -> Result<Option<Duration>, SqliteTrackerError> {
+        let mut conn = self.connect()?;
+        let tx = begin(&mut conn)?;
+
+        let Some(session) = running_session(&tx)? else {
+            return Ok(None);
+        };
+        tx.execute("DELETE FROM running", [])
+            .change_context(SqliteTrackerError)
+            .attach_printable("failed to delete running session")?;
+        commit(tx)?;
+
+        Ok(Some(session.finish(EndTime::now()).duration()))
+    }
+
+    fn add_record_impl(
+        &self,
+        record: TimeRecord,
+        overlap: Overlap,
+    ) -> Result<Vec<TimeRecord>, SqliteTrackerError> {
+        let mut conn = self.connect()?;
+        let tx = begin(&mut conn)?;
+
+        let running = running_session(&tx)?.map(|session| session.snapshot(Utc::now()));
+        let existing = overlapping_records(&tx, &record)?
+            .into_iter()
+            .chain(running);
+        let overlapping =
+            check_new_record(&record, existing, overlap).change_context(SqliteTrackerError)?;
+        insert_record(&tx, &record)?;
+        commit(tx)?;
+
+        Ok(overlapping)
+    }
+
+    fn edit_record_impl(
+        &self,
+        id: RecordId,
+        edit: RecordEdit,
+    ) -> Result<TimeRecord, SqliteTrackerError> {
+        let mut conn = self.connect()?;
+        let tx = begin(&mut conn)?;
+
+        let record = find_record(&tx, id)?;
+        let record = edit.apply(record).change_context(SqliteTrackerError)?;
+        update_record(&tx, &record)?;
+        commit(tx)?;
+
+        Ok(record)
+    }
+
+    fn delete_record_impl(&self, id: RecordId) -> Result<TimeRecord, SqliteTrackerError> {
+        let mut conn = self.connect()?;
+        let tx = begin(&mut conn)?;
+
+        let record = find_record(&tx, id)?;
+        tx.execute("DELETE FROM records WHERE ulid = ?1", [id.to_string()])
+            .change_context(SqliteTrackerError)
+            .attach_printable("failed to delete record")?;
+        commit(tx)?;
+
+        Ok(record)
+    }
+
+    fn apply_changes_impl(&self, changes: RecordChanges) -> Result<(), SqliteTrackerError> {
+        let mut conn = self.connect()?;
+        let tx = begin(&mut conn)?;
+
+        let affected = changes
+            .deletions
+            .iter()
+            .chain(changes.edits.iter().map(|(id, _)| id))
+            .map(|&id| find_record(&tx, id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let changes = changes
+            .resolve(affected)
+            .change_context(SqliteTrackerError)?;
+        let mut existing = Vec::new();
+        for record in changes.changed() {
+            existing.extend(overlapping_records(&tx, record)?);
+        }
+        let running = running_session(&tx)?.map(|session| session.snapshot(Utc::now()));
+        changes
+            .check_overlaps(existing.into_iter().chain(running))
+            .change_context(SqliteTrackerError)?;
+
+        for record in &changes.deleted {
+            tx.execute(
+                "DELETE FROM records WHERE ulid = ?1",
+                [record.id.to_string()],
+            )
+            .change_context(SqliteTrackerError)
+            .attach_printable("failed to delete record")?;
+        }
+        for (_, record) in &changes.edited {
+            update_record(&tx, record)?;
+        }
+        for record in &changes.added {
+            insert_record(&tx, record)?;
+        }
+        commit(tx)
+    }
+
+    fn switch_impl(&self, details: SessionDetails) -> Result<(), SqliteTrackerError> {
+        let mut conn = self.connect()?;
+        let tx = begin(&mut conn)?;
+
+        let now = Utc::now();
+        if let Some(session) = running_session(&tx)? {
+            insert_record(&tx, &session.finish(EndTime(now)))?;
+        }
+        let session = LockfileData::new(StartTime(now), details);
+        tx.execute(
+            &format!(
+                "INSERT OR REPLACE INTO running (id, {RUNNING_COLUMNS})
+                 VALUES (0, ?1, ?2, ?3, ?4, ?5, ?6)"
+            ),
+            running_params(&session)?,
+        )
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to replace running session")?;
+
+        commit(tx)
+    }
+
+    /// Applies `change` to the running session
+    fn update_session<F, T>(&self, change: F) -> Result<T, SqliteTrackerError>
+    where
+        F: FnOnce(&mut LockfileData) -> T,
+    {
+        let mut conn = self.connect()?;
+        let tx = begin(&mut conn)?;
+
+        let mut session = running_session(&tx)?
+            .ok_or(SqliteTrackerError)
+            .attach_printable("the tracker is not running")
+            .attach(Suggestion("start tracking first with `track start`"))?;
+        let result = change(&mut session);
+        tx.execute(
+            &format!(
+                "UPDATE running SET ({RUNNING_COLUMNS}) = (?1, ?2, ?3, ?4, ?5, ?6) WHERE id = 0"
+            ),
+            running_params(&session)?,
+        )
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to update running session")?;
+
+        commit(tx)?;
+        Ok(result)
+    }
+
+    fn state_impl(&self) -> Result<TrackerState, SqliteTrackerError> {
+        let conn = self.connect()?;
+        Ok(running_session(&conn)?.map_or(TrackerState::Stopped, |session| session.state()))
+    }
+
+    fn current_session_impl(&self) -> Result<Option<LockfileData>, SqliteTrackerError> {
+        let conn = self.connect()?;
+        running_session(&conn)
+    }
+
+    /// Records matching the SQL `condition`, ordered by start time
+    fn records_impl<P>(
+        &self,
+        condition: &str,
+        params: P,
+    ) -> Result<Vec<TimeRecord>, SqliteTrackerError>
+    where
+        P: Params,
+    {
+        let conn = self.connect()?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {RECORD_COLUMNS} FROM records WHERE {condition} ORDER BY start_ms"
+            ))
+            .change_context(SqliteTrackerError)
+            .attach_printable("failed to prepare records query")?;
+
+        let rows = stmt
+            .query_map(params, record_from_row)
+            .change_context(SqliteTrackerError)
+            .attach_printable("failed to query records")?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            let record = row
+                .change_context(SqliteTrackerError)
+                .attach_printable("failed to read record")?;
+            records.push(record);
+        }
+        Ok(records)
+    }
+}
+
+impl Tracker for SqliteTracker {
+    fn start_at(
+        &mut self,
+        start: StartTime,
+        details: SessionDetails,
+    ) -> Result<StartupStatus, TrackerError> {
+        self.start_impl(start, details).change_context(TrackerError)
+    }
+
+    fn stop_at(&mut self, end: EndTime, description: Option<String>) -> Result<(), TrackerError> {
+        self.stop_impl(end, description)
+            .change_context(TrackerError)
+    }
+
+    fn set_description(&mut self, description: String) -> Result<(), TrackerError> {
+        self.update_session(|session| session.details.description = Some(description))
+            .change_context(TrackerError)
+    }
+
+    fn cancel(&mut self) -> Result<Option<Duration>, TrackerError> {
+        self.cancel_impl().change_context(TrackerError)
+    }
+
+    fn add_record(
+        &mut self,
+        record: TimeRecord,
+        overlap: Overlap,
+    ) -> Result<Vec<TimeRecord>, TrackerError> {
+        self.add_record_impl(record, overlap)
+            .change_context(TrackerError)
+    }
+
+    fn edit_record(&mut self, id: RecordId, edit: RecordEdit) -> Result<TimeRecord, TrackerError> {
+        self.edit_record_impl(id, edit).change_context(TrackerError)
+    }
+
+    fn delete_record(&mut self, id: RecordId) -> Result<TimeRecord, TrackerError> {
+        self.delete_record_impl(id).change_context(TrackerError)
+    }
+
+    fn apply_changes(&mut self, changes: RecordChanges) -> Result<(), TrackerError> {
+        self.apply_changes_impl(changes)
+            .change_context(TrackerError)
+    }
+
+    fn switch(&mut self, details: SessionDetails) -> Result<(), TrackerError> {
+        self.switch_impl(details).change_context(TrackerError)
+    }
+
+    fn pause(&mut self) -> Result<PauseStatus, TrackerError> {
+        self.update_session(|session| session.pause(Utc::now()))
+            .change_context(TrackerError)
+    }
+
+    fn resume(&mut self) -> Result<ResumeStatus, TrackerError> {
+        self.update_session(|session| session.resume(Utc::now()))
+            .change_context(TrackerError)
+    }
+
+    fn state(&self) -> Result<TrackerState, TrackerError> {
+        self.state_impl().change_context(TrackerError)
+    }
+
+    fn current_session(&self) -> Result<Option<LockfileData>, TrackerError> {
+        self.current_session_impl().change_context(TrackerError)
+    }
+
+    fn records(&self) -> Result<impl Iterator<Item = TimeRecord>, TrackerError> {
+        let records = self.records_impl("1", []).change_context(TrackerError)?;
+        Ok(records.into_iter())
+    }
+
+    fn records_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<impl Iterator<Item = TimeRecord>, TrackerError> {
+        let records = self
+            .records_impl("start_ms >= ?1", params![since.timestamp_millis()])
+            .change_context(TrackerError)?;
+        Ok(records.into_iter())
+    }
+
+    fn records_overlapping(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<impl Iterator<Item = TimeRecord>, TrackerError> {
+        let records = self
+            .records_impl(
+                "start_ms < ?2 AND end_ms > ?1",
+                params![from.timestamp_millis(), to.timestamp_millis()],
+            )
+            .change_context(TrackerError)?;
+        Ok(records.into_iter())
+    }
+}
+
+impl Reporter for SqliteTracker {}
+
+fn migrate(conn: &mut Connection) -> Result<(), SqliteTrackerError> {
+    let version = schema_version(conn)?;
+    ensure_supported(version)?;
+    if version == MIGRATIONS.len() {
+        return Ok(());
+    }
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Exclusive)
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to begin migration")?;
+    // another process may have migrated while we waited for the lock
+    let version = schema_version(&tx)?;
+    ensure_supported(version)?;
+    for (migration, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+        tx.execute_batch(sql)
+            .change_context(SqliteTrackerError)
+            .attach_printable_lazy(|| format!("failed to apply migration {}", migration + 1))?;
+    }
+    backfill_record_ids(&tx)?;
+    tx.pragma_update(None, "user_version", MIGRATIONS.len())
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to update schema version")?;
+    tx.commit()
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to commit migration")
+}
+
+/// Refuses databases written by a newer release
+fn ensure_supported(version: usize) -> Result<(), SqliteTrackerError> {
+    if version > MIGRATIONS.len() {
+        return Err(SqliteTrackerError)
+            .attach_printable(format!(
+                "database schema version {version} is newer than the supported version {}",
+                MIGRATIONS.len()
+            ))
+            .attach(Suggestion(
+                "the database was written by a newer release; upgrade `track` to read it",
+            ));
+    }
+    Ok(())
+}
+
+/// Gives records inserted before they had identifiers one
+fn backfill_record_ids(conn: &Connection) -> Result<(), SqliteTrackerError> {
+    let mut stmt = conn
+        .prepare("SELECT id, start_ms FROM records WHERE ulid IS NULL")
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to prepare record id query")?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to query records without id")?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to read records without id")?;
+
+    for (rowid, start_ms) in rows {
+        let id = RecordId::legacy(start_ms, rowid as u64);
+        conn.execute(
+            "UPDATE records SET ulid = ?1 WHERE id = ?2",
+            params![id.to_string(), rowid],
+        )
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to store record id")?;
+    }
+    Ok(())
+}
+
+fn schema_version(conn: &Connection) -> Result<usize, SqliteTrackerError> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to read schema version")
+}
+
+fn begin(conn: &mut Connection) -> Result<Transaction<'_>, SqliteTrackerError> {
+    conn.transaction_with_behavior(TransactionBehavior::Immediate)
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to begin transaction")
+}
+
+fn commit(tx: Transaction<'_>) -> Result<(), SqliteTrackerError> {
+    tx.commit()
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to commit transaction")
+}
+
+/// The session being tracked, if any
+fn running_session(conn: &Connection) -> Result<Option<LockfileData>, SqliteTrackerError> {
+    conn.query_row(
+        &format!("SELECT {RUNNING_COLUMNS} FROM running WHERE id = 0"),
+        [],
+        |row| {
+            Ok(LockfileData {
+                start_time: StartTime(millis_column(row, 0)?),
+                details: SessionDetails {
+                    project: row.get(1)?,
+                    tags: json_column(row, 2)?,
+                    description: row.get(3)?,
+                },
+                breaks: json_column(row, 4)?,
+                paused_at: row
+                    .get::<_, Option<i64>>(5)?
+                    .map(|ms| from_millis(5, ms))
+                    .transpose()?,
+            })
+        },
+    )
+    .optional()
+    .change_context(SqliteTrackerError)
+    .attach_printable("failed to read running session")
+}
+
+/// Parameters for the [`RUNNING_COLUMNS`]
+fn running_params(session: &LockfileData) -> Result<impl Params, SqliteTrackerError> {
+    Ok((
+        session.start_time.timestamp_millis(),
+        session.details.project.clone(),
+        encode_json(&session.details.tags)?,
+        session.details.description.clone(),
+        encode_json(&session.breaks)?,
+        session.paused_at.map(|at| at.timestamp_millis()),
+    ))
+}
+
+/// Parameters for the [`RECORD_COLUMNS`] of `record`
+fn record_params(record: &TimeRecord) -> Result<impl Params, SqliteTrackerError> {
+    Ok((
+        record.start.timestamp_millis(),
+        record.end.timestamp_millis(),
+        record.details.project.clone(),
+        encode_json(&record.details.tags)?,
+        record.details.description.clone(),
+        encode_json(&record.breaks)?,
+        record.id.to_string(),
+    ))
+}
+
+fn insert_record(conn: &Connection, record: &TimeRecord) -> Result<(), SqliteTrackerError> {
+    conn.execute(
+        &format!("INSERT INTO records ({RECORD_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"),
+        record_params(record)?,
+    )
+    .change_context(SqliteTrackerError)
+    .attach_printable("failed to insert record")?;
+    Ok(())
+}
+
+fn find_record(conn: &Connection, id: RecordId) -> Result<TimeRecord, SqliteTrackerError> {
+    conn.query_row(
+        &format!("SELECT {RECORD_COLUMNS} FROM records WHERE ulid = ?1"),
+        [id.to_string()],
+        record_from_row,
+    )
+    .optional()
+    .change_context(SqliteTrackerError)
+    .attach_printable("failed to query record")?
+    .ok_or_else(|| unknown_record(id))
+    .change_context(SqliteTrackerError)
+}
+
+/// Replaces the stored record with the same identifier
+fn update_record(conn: &Connection, record: &TimeRecord) -> Result<(), SqliteTrackerError> {
+    // the identifier is the last column and doubles as the key
+    conn.execute(
+        &format!(
+            "UPDATE records SET ({RECORD_COLUMNS}) = (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             WHERE ulid = ?7"
+        ),
+        record_params(record)?,
+    )
+    .change_context(SqliteTrackerError)
+    .attach_printable("failed to update record")?;
+    Ok(())
+}
+
+/// Records covering some of the time of `record`
+fn overlapping_records(
+    conn: &Connection,
+    record: &TimeRecord,
+) -> Result<Vec<TimeRecord>, SqliteTrackerError> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {RECORD_COLUMNS} FROM records WHERE start_ms < ?1 AND end_ms > ?2"
+        ))
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to prepare overlap query")?;
+    let rows = stmt
+        .query_map(
+            params![
+                record.end.timestamp_millis(),
+                record.start.timestamp_millis()
+            ],
+            record_from_row,
+        )
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to query overlapping records")?;
+
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to read record")
+}
+
+/// Reads a record selected with [`RECORD_COLUMNS`]
+fn record_from_row(row: &Row<'_>) -> rusqlite::Result<TimeRecord> {
+    Ok(TimeRecord {
+        id: id_column(row, 6)?,
+        start: StartTime(millis_column(row, 0)?),
+        end: EndTime(millis_column(row, 1)?),
+        details: SessionDetails {
+            project: row.get(2)?,
+            tags: json_column(row, 3)?,
+            description: row.get(4)?,
+        },
+        breaks: json_column(row, 5)?,
+    })
+}
+
+fn id_column(row: &Row<'_>, index: usize) -> rusqlite::Result<RecordId> {
+    let id: String = row.get(index)?;
+    id.parse()
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
+
+fn millis_column(row: &Row<'_>, index: usize) -> rusqlite::Result<DateTime<Utc>> {
+    from_millis(index, row.get(index)?)
+}
+
+fn from_millis(index: usize, ms: i64) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(ms).ok_or(rusqlite::Error::IntegralValueOutOfRange(index, ms))
+}
+
+/// Tags and breaks are stored as JSON
+fn json_column<T>(row: &Row<'_>, index: usize) -> rusqlite::Result<T>
+where
+    T: DeserializeOwned,
+{
+    let json: String = row.get(index)?;
+    serde_json::from_str(&json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
+
+fn encode_json<T>(value: &T) -> Result<String, SqliteTrackerError>
+where
+    T: Serialize,
+{
+    serde_json::to_string(value)
+        .change_context(SqliteTrackerError)
+        .attach_printable("failed to serialize column")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use assert_fs::{fixture::ChildPath, prelude::PathChild, TempDir};
+
+    use super::*;
+
+    fn temp_db() -> (TempDir, ChildPath) {
+        let temp = TempDir::new().unwrap();
+        let db = temp.child("db.sqlite3");
+        (temp, db)
+    }
+
+    fn new_sqlite_tracker(db: &ChildPath) -> SqliteTracker {
+        SqliteTracker::new(db.to_path_buf())
+    }
+
+    #[test]
+    fn is_running_returns_true_after_start() {
+        let (_temp, db) = temp_db();
+
+        // Given a default tracker
+        let mut tracker = new_sqlite_tracker(&db);
+
+        // When the tracker is started
+        tracker.start().unwrap();
+
+        // Then the tracker is running
+        assert!(tracker.is_running());
+    }
+
+    #[test]
+    fn is_running_returns_false_after_stop() {
+        let (_temp, db) = temp_db();
+
+        // Given a running new tracker
+        let mut tracker = new_sqlite_tracker(&db);
+        tracker.start().unwrap();
+
+        // When the tracker is stopped
+        tracker.stop().unwrap();
+
+        // Then the tracker is not running
+        assert!(!tracker.is_running());
+    }
+
+    #[test]
+    fn multiple_starts_return_already_running_state() {
+        // Given a running tracker
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+        tracker.start().unwrap();
+
+        // When the tracker is started again
+        let started = tracker.start().unwrap();
+
+        // Then the `already_running` state is returned
+        assert_eq!(started, StartupStatus::Running);
+    }
+
+    #[test]
+    fn stop_fails_when_not_running() {
+        // Given a new tracker
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+
+        // When the tracker is stopped
+        let stopped = tracker.stop();
+
+        // Then an error is returned
+        assert!(stopped.is_err());
+    }
+
+    #[test]
+    fn records_overlapping_includes_records_started_earlier() {
+        // Given a record from two hours ago until an hour ago
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+        let now = Utc::now();
+        let record = TimeRecord::new(
+            StartTime::at(now - chrono::Duration::hours(2)),
+            EndTime::at(now - chrono::Duration::hours(1)),
+            SessionDetails::default(),
+        );
+        tracker.add_record(record, Overlap::Allow).unwrap();
+
+        // When records overlapping the last 90 minutes or the last 30 are requested
+        let recent = tracker
+            .records_overlapping(now - chrono::Duration::minutes(90), now)
+            .unwrap();
+        let latest = tracker
+            .records_overlapping(now - chrono::Duration::minutes(30), now)
+            .unwrap();
+
+        // Then the record only overlaps the former
+        assert_eq!(recent.count(), 1);
+        assert_eq!(latest.count(), 0);
+    }
+
+    #[test]
+    fn records_since_skips_older_records() {
+        // Given a tracker with a record
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+        tracker.start().unwrap();
+        tracker.stop().unwrap();
+
+        // When records starting in the future are requested
+        let future = Utc::now() + chrono::Duration::hours(1);
+        let mut records = tracker.records_since(future).unwrap();
+
+        // Then there are none, while all records are still available
+        assert!(records.next().is_none());
+        assert_eq!(tracker.records().unwrap().count(), 1);
+    }
+
+    #[test]
+    fn project_and_tags_are_kept_on_the_record() {
+        // Given a tracker started with a project and a tag
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+        let details = SessionDetails {
+            project: Some("foo".to_string()),
+            tags: BTreeSet::from(["review".to_string()]),
+            ..Default::default()
+        };
+        tracker.start_with(details).unwrap();
+
+        // When the tracker is stopped
+        tracker.stop().unwrap();
+
+        // Then the record carries the project and tag
+        let record = tracker.records().unwrap().next().unwrap();
+        assert_eq!(record.project(), Some("foo"));
+        assert!(record.tags().contains("review"));
+    }
+
+    #[test]
+    fn descriptions_from_start_note_and_stop_are_combined() {
+        // Given a tracker started with a description that is then amended
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+        let details = SessionDetails {
+            description: Some("fixing bug".to_string()),
+            ..Default::default()
+        };
+        tracker.start_with(details).unwrap();
+        tracker
+            .set_description("fixing login bug".to_string())
+            .unwrap();
+
+        // When the tracker is stopped with a description
+        tracker.stop_with(Some("PR opened".to_string())).unwrap();
+
+        // Then the record holds both descriptions
+        let record = tracker.records().unwrap().next().unwrap();
+        assert_eq!(record.description(), Some("fixing login bug; PR opened"));
+    }
+
+    #[test]
+    fn paused_tracker_is_not_running() {
+        // Given a running tracker
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+        tracker.start().unwrap();
+
+        // When the tracker is paused
+        let paused = tracker.pause().unwrap();
+
+        // Then it is paused rather than running
+        assert_eq!(paused, PauseStatus::Paused);
+        assert_eq!(tracker.state().unwrap(), TrackerState::Paused);
+        assert!(!tracker.is_running());
+    }
+
+    #[test]
+    fn breaks_are_kept_on_the_record() {
+        // Given a tracker that was paused and resumed
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+        tracker.start().unwrap();
+        tracker.pause().unwrap();
+        assert_eq!(tracker.resume().unwrap(), ResumeStatus::Resumed);
+
+        // When the tracker is stopped
+        tracker.stop().unwrap();
+
+        // Then the record holds the break
+        let record = tracker.records().unwrap().next().unwrap();
+        assert_eq!(record.breaks().len(), 1);
+    }
+
+    #[test]
+    fn switch_leaves_no_gap_between_records() {
+        // Given a running tracker
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+        tracker.start().unwrap();
+
+        // When switching to another project and stopping
+        let details = SessionDetails {
+            project: Some("foo".to_string()),
+            ..Default::default()
+        };
+        tracker.switch(details).unwrap();
+        tracker.stop().unwrap();
+
+        // Then the second record starts where the first one ended
+        let records = tracker.records().unwrap().collect::<Vec<_>>();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].end(), records[1].start());
+        assert_eq!(records[1].project(), Some("foo"));
+    }
+
+    #[test]
+    fn cancel_discards_running_session() {
+        // Given a running tracker
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+        tracker.start().unwrap();
+
+        // When cancelling
+        let discarded = tracker.cancel().unwrap();
+
+        // Then the session is gone without leaving a record
+        assert!(discarded.is_some());
+        assert_eq!(tracker.state().unwrap(), TrackerState::Stopped);
+        assert_eq!(tracker.records().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn overlapping_records_are_found_by_query() {
+        // Given a record from three hours ago until two hours ago
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+        let start = Utc::now() - chrono::TimeDelta::hours(3);
+        let hour = chrono::TimeDelta::hours(1);
+        let record =
+            |start, end| TimeRecord::new(StartTime(start), EndTime(end), SessionDetails::default());
+        tracker
+            .add_record(record(start, start + hour), Overlap::Allow)
+            .unwrap();
+
+        // When adding one right after it and one overlapping it
+        let adjacent = tracker
+            .add_record(record(start + hour, start + hour * 2), Overlap::Refuse)
+            .unwrap();
+        let overlapping = tracker
+            .add_record(record(start - hour, start + hour / 2), Overlap::Allow)
+            .unwrap();
+
+        // Then only the overlap is reported
+        assert!(adjacent.is_empty());
+        assert_eq!(overlapping.len(), 1);
+        assert_eq!(tracker.records().unwrap().count(), 3);
+    }
+
+    #[test]
+    fn running_session_counts_as_overlap() {
+        // Given a session running for an hour
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+        let started = Utc::now() - chrono::TimeDelta::hours(1);
+        tracker
+            .start_at(StartTime(started), SessionDetails::default())
+            .unwrap();
+
+        // When adding a record from half an hour ago
+        let overlapping = tracker
+            .add_record(
+                TimeRecord::new(
+                    StartTime(started + chrono::TimeDelta::minutes(30)),
+                    EndTime(Utc::now()),
+                    SessionDetails::default(),
+                ),
+                Overlap::Allow,
+            )
+            .unwrap();
+
+        // Then the running session is reported
+        assert_eq!(overlapping.len(), 1);
+        assert!(overlapping[0].is_running());
+    }
+
+    #[test]
+    fn records_from_before_identifiers_are_backfilled() {
+        // Given a database with a record stored before records had identifiers
+        let (_temp, db) = temp_db();
+        let conn = Connection::open(db.path()).unwrap();
+        for sql in &MIGRATIONS[..4] {
+            conn.execute_batch(sql).unwrap();
+        }
+        conn.pragma_update(None, "user_version", 4).unwrap();
+        conn.execute(
+            "INSERT INTO records (start_ms, end_ms) VALUES (?1, ?2)",
+            params![1_000, 2_000],
+        )
+        .unwrap();
+        drop(conn);
+
+        // When reading the records twice
+        let tracker = new_sqlite_tracker(&db);
+        let first = tracker.records().unwrap().next().unwrap();
+        let second = tracker.records().unwrap().next().unwrap();
+
+        // Then the record has kept the identifier it was given
+        assert_ne!(first.id(), RecordId::default());
+        assert_eq!(first.id(), second.id());
+    }
+
+    #[test]
+    fn database_from_newer_release_is_refused() {
+        // Given a database with a schema newer than any migration
+        let (_temp, db) = temp_db();
+        let tracker = new_sqlite_tracker(&db);
+        let conn = tracker.connect().unwrap();
+        conn.pragma_update(None, "user_version", MIGRATIONS.len() + 1)
+            .unwrap();
+        drop(conn);
+
+        // When reading the records
+        let records = tracker.records();
+
+        // Then it fails and leaves the schema version alone
+        assert!(records.is_err());
+        let conn = Connection::open(db.path()).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.len() + 1);
+    }
+
+    #[test]
+    fn edited_record_keeps_its_identifier() {
+        // Given a recorded session
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+        tracker.start().unwrap();
+        tracker.stop().unwrap();
+        let id = tracker.records().unwrap().next().unwrap().id();
+
+        // When moving its start an hour back
+        let start = Utc::now() - chrono::TimeDelta::hours(1);
+        let edit = RecordEdit {
+            start: Some(StartTime(start)),
+            ..Default::default()
+        };
+        tracker.edit_record(id, edit).unwrap();
+
+        // Then the stored record has the new start under the same identifier
+        let record = tracker.records().unwrap().next().unwrap();
+        assert_eq!(record.id(), id);
+        assert_eq!(record.start().timestamp_millis(), start.timestamp_millis());
+    }
+
+    #[test]
+    fn deleting_unknown_record_fails() {
+        // Given a tracker without records
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+
+        // When deleting a record
+        let result = tracker.delete_record(RecordId::default());
+
+        // Then it fails
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn changes_overlapping_stored_records_are_refused() {
+        // Given two records an hour apart
+        let (_temp, db) = temp_db();
+        let mut tracker = new_sqlite_tracker(&db);
+        let start = Utc::now() - chrono::TimeDelta::hours(4);
+        let hour = chrono::TimeDelta::hours(1);
+        let record =
+            |start, end| TimeRecord::new(StartTime(start), EndTime(end), SessionDetails::default());
+        let first = record(start, start + hour);
+        tracker.add_record(first.clone(), Overlap::Allow).unwrap();
+        tracker
+            .add_record(record(start + hour * 2, start + hour * 3), Overlap::Allow)
+            .unwrap();
+
+        // When moving the first onto the second, then into the gap between them
+        let edit = RecordEdit {
+            start: Some(StartTime(start + hour * 2)),
+            end: Some(EndTime(start + hour * 3)),
+            ..Default::default()
+        };
+        let moved = tracker.apply_changes(RecordChanges {
+            edits: vec![(first.id(), edit)],
+            ..Default::default()
+        });
+        let moved_elsewhere = tracker.apply_changes(RecordChanges {
+            edits: vec![(
+                first.id(),
+                RecordEdit {
+                    start: Some(StartTime(start + hour)),
+                    end: Some(EndTime(start + hour * 2)),
+                    ..Default::default()
+                },
+            )],
+            ..Default::default()
+        });
+
+        // Then only the move into the gap is applied
+        assert!(moved.is_err());
+        assert!(moved_elsewhere.is_ok());
+        let record = tracker.records().unwrap().next().unwrap();
+        assert_eq!(record.id(), first.id());
+        assert_eq!(
+            record.start().timestamp_millis(),
+            (start + hour).timestamp_millis()
+        );
+    }
+
+    #[test]
+    fn overlap_queries_use_an_index() {
+        // Given a database at the latest schema
+        let (_temp, db) = temp_db();
+        let tracker = new_sqlite_tracker(&db);
+        let conn = tracker.connect().unwrap();
+
+        // When planning the query for records overlapping a timespan
+        let plan = conn
+            .prepare("EXPLAIN QUERY PLAN SELECT * FROM records WHERE start_ms < ?2 AND end_ms > ?1")
+            .unwrap()
+            .query_map([0, 1], |row| row.get::<_, String>(3))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .unwrap();
+
+        // Then it searches by end, as recent timespans are asked for most
+        assert_eq!(
+            plan,
+            ["SEARCH records USING INDEX records_end_ms (end_ms>?)"]
+        );
+    }
+}
@@ -1,5 +1,9 @@
 use assert_cmd::Command;
-use assert_fs::{fixture::ChildPath, prelude::PathChild, TempDir};
+use assert_fs::{
+    fixture::ChildPath,
+    prelude::{FileWriteStr, PathChild},
+    TempDir,
+};
 use testresult::TestResult;
 
 fn temp_paths() -> (TempDir, ChildPath, ChildPath) {
@@ -9,27 +13,31 @@ fn temp_paths() -> (TempDir, ChildPath, ChildPath) {
     (temp, db, lockfile)
 }
 
-fn start_tracking(db: &ChildPath, lockfile: &ChildPath) -> Result<(), testresult::TestError> {
-    Command::cargo_bin("track")?
+/// `track` using the database and lockfile of a test, with times in UTC
+///
+/// Settings come from `config.toml` next to the database, if the test writes
+/// one, never from the environment or config of whoever runs the tests.
+fn track(db: &ChildPath, lockfile: &ChildPath) -> Result<Command, testresult::TestError> {
+    let mut cmd = Command::cargo_bin("track")?;
+    cmd.env("TZ", "UTC")
+        .env("TRACK_CONFIG", db.path().with_file_name("config.toml"))
+        .env_remove("TRACK_TZ")
+        .env_remove("TRACK_DURATION_FORMAT")
+        .env_remove("TRACK_WEEK_START")
         .arg("--db-dir")
         .arg(db.to_path_buf())
         .arg("--lockfile")
-        .arg(lockfile.to_path_buf())
-        .arg("start")
-        .assert()
-        .success();
+        .arg(lockfile.to_path_buf());
+    Ok(cmd)
+}
+
+fn start_tracking(db: &ChildPath, lockfile: &ChildPath) -> Result<(), testresult::TestError> {
+    track(db, lockfile)?.arg("start").assert().success();
     Ok(())
 }
 
 fn stop_tracking(db: &ChildPath, lockfile: &ChildPath) -> Result<(), testresult::TestError> {
-    Command::cargo_bin("track")?
-        .arg("--db-dir")
-        .arg(db.to_path_buf())
-        .arg("--lockfile")
-        .arg(lockfile.to_path_buf())
-        .arg("stop")
-        .assert()
-        .success();
+    track(db, lockfile)?.arg("stop").assert().success();
     Ok(())
 }
 
@@ -65,11 +73,7 @@ fn report_command_generates_report() -> TestResult {
     start_tracking(&db, &lockfile)?;
     stop_tracking(&db, &lockfile)?;
 
-    Command::cargo_bin("track")?
-        .arg("--db-dir")
-        .arg(db.to_path_buf())
-        .arg("--lockfile")
-        .arg(lockfile.to_path_buf())
+    track(&db, &lockfile)?
         .arg("report")
         .assert()
         .stdout("00:00:00\n")
@@ -77,3 +81,688 @@ fn report_command_generates_report() -> TestResult {
 
     Ok(())
 }
+
+#[test]
+fn sqlite_backend_records_sessions() -> TestResult {
+    let temp = TempDir::new()?;
+    let db = temp.child("db.sqlite3");
+
+    for command in ["start", "stop", "report"] {
+        Command::cargo_bin("track")?
+            .env("TRACK_CONFIG", temp.child("config.toml").path())
+            .arg("--backend")
+            .arg("sqlite")
+            .arg("--db-dir")
+            .arg(db.to_path_buf())
+            .arg(command)
+            .assert()
+            .success();
+    }
+
+    assert!(db.to_path_buf().exists());
+    Ok(())
+}
+
+#[test]
+fn journal_backend_can_be_compacted() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+
+    for command in ["start", "stop", "compact"] {
+        track(&db, &lockfile)?
+            .arg("--backend")
+            .arg("journal")
+            .arg(command)
+            .assert()
+            .success();
+    }
+
+    assert_eq!(std::fs::read_to_string(&db)?.lines().count(), 1);
+    Ok(())
+}
+
+#[test]
+fn compact_fails_for_other_backends() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+
+    track(&db, &lockfile)?.arg("compact").assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn report_can_be_grouped_by_project() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+
+    track(&db, &lockfile)?
+        .args(["start", "--project", "foo", "--tag", "review"])
+        .assert()
+        .success();
+    stop_tracking(&db, &lockfile)?;
+
+    track(&db, &lockfile)?
+        .args(["report", "--tag", "review", "--by", "project"])
+        .assert()
+        .stdout("foo    00:00:00\ntotal  00:00:00\n")
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn report_lists_session_notes() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+
+    for args in [
+        vec!["start", "-m", "fixing login bug"],
+        vec!["stop", "-m", "done, PR opened"],
+    ] {
+        track(&db, &lockfile)?.args(args).assert().success();
+    }
+
+    let output = track(&db, &lockfile)?
+        .args(["report", "--notes"])
+        .output()?;
+
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?
+        .ends_with("00:00:00  fixing login bug; done, PR opened\n"));
+
+    Ok(())
+}
+
+#[test]
+fn switch_command_starts_a_new_session() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+
+    for args in [
+        vec!["start", "--project", "foo"],
+        vec!["switch", "--project", "bar"],
+    ] {
+        track(&db, &lockfile)?.args(args).assert().success();
+    }
+    stop_tracking(&db, &lockfile)?;
+
+    track(&db, &lockfile)?
+        .args(["report", "--by", "project"])
+        .assert()
+        .stdout("bar    00:00:00\nfoo    00:00:00\ntotal  00:00:00\n")
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn cancel_command_discards_session() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+    start_tracking(&db, &lockfile)?;
+
+    track(&db, &lockfile)?
+        .arg("cancel")
+        .assert()
+        .stdout("discarded 00:00:00\n")
+        .success();
+
+    assert!(!lockfile.to_path_buf().exists());
+    Ok(())
+}
+
+#[test]
+fn start_can_be_backdated() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+
+    for args in [vec!["start", "--ago", "1h"], vec!["stop", "--ago", "30m"]] {
+        track(&db, &lockfile)?.args(args).assert().success();
+    }
+
+    track(&db, &lockfile)?
+        .arg("report")
+        .assert()
+        .stdout("00:30:00\n")
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn stop_before_start_fails() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+    start_tracking(&db, &lockfile)?;
+
+    track(&db, &lockfile)?
+        .args(["stop", "--at", "1h ago"])
+        .assert()
+        .failure();
+
+    assert!(lockfile.to_path_buf().exists());
+    Ok(())
+}
+
+#[test]
+fn add_command_records_past_session() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+
+    let add = |extra: &[&str]| -> Result<assert_cmd::assert::Assert, testresult::TestError> {
+        Ok(track(&db, &lockfile)?
+            .args([
+                "add",
+                "--from",
+                "2h ago",
+                "--to",
+                "1h ago",
+                "--project",
+                "meeting",
+            ])
+            .args(extra)
+            .assert())
+    };
+
+    add(&[])?.stdout("").stderr("").success();
+    add(&["--no-overlap"])?.failure();
+    add(&[])?
+        .stdout("")
+        .stderr("warning: the record overlaps 1 session(s)\n")
+        .success();
+
+    track(&db, &lockfile)?
+        .args(["report", "--by", "project"])
+        .assert()
+        .stdout("meeting  02:00:00\ntotal    02:00:00\n")
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn log_command_lists_records() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+
+    for (from, to, project) in [
+        ("2026-10-16 09:00", "2026-10-16 10:00", "acme"),
+        ("2026-10-17 09:00", "2026-10-17 11:30", "initech"),
+    ] {
+        track(&db, &lockfile)?
+            .args(["add", "--from", from, "--to", to, "--project", project])
+            .assert()
+            .success();
+    }
+
+    let output = track(&db, &lockfile)?
+        .args(["log", "--since", "2026-10-16", "-n", "1"])
+        .output()?;
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].ends_with("  2026-10-17 09:00  2026-10-17 11:30  02:30:00  initech"));
+    Ok(())
+}
+
+#[test]
+fn records_can_be_edited_and_deleted() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+    track(&db, &lockfile)?
+        .args([
+            "add",
+            "--from",
+            "2026-10-17 09:00",
+            "--to",
+            "2026-10-17 10:00",
+        ])
+        .assert()
+        .success();
+    let log = String::from_utf8(track(&db, &lockfile)?.arg("log").output()?.stdout)?;
+    let id = log.split_whitespace().next().ok_or("no record listed")?;
+
+    track(&db, &lockfile)?
+        .args(["edit", id, "--end", "2026-10-17 08:00"])
+        .assert()
+        .failure();
+    track(&db, &lockfile)?
+        .args(["edit", id, "--end", "2026-10-17 11:00", "--project", "acme"])
+        .assert()
+        .stdout(format!(
+            "{id}  2026-10-17 09:00  2026-10-17 11:00  02:00:00  acme\n"
+        ))
+        .success();
+    track(&db, &lockfile)?
+        .args(["delete", id])
+        .assert()
+        .success();
+    track(&db, &lockfile)?
+        .arg("log")
+        .assert()
+        .stdout("")
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn records_can_be_edited_interactively() -> TestResult {
+    let (temp, db, lockfile) = temp_paths();
+    // breaks the end time, then fixes it once the problem is reported
+    let editor = temp.child("editor.sh");
+    std::fs::write(
+        editor.path(),
+        "if grep -q '^# error:' \"$1\"; then sed -i 's/08:00/11:00/' \"$1\"; \
+         else sed -i 's/acme/initech/; s/10:00/08:00/' \"$1\"; fi\n",
+    )?;
+    track(&db, &lockfile)?
+        .args([
+            "add",
+            "--from",
+            "2026-10-17 09:00",
+            "--to",
+            "2026-10-17 10:00",
+            "--project",
+            "acme",
+        ])
+        .assert()
+        .success();
+
+    track(&db, &lockfile)?
+        .env_remove("VISUAL")
+        .env("EDITOR", format!("sh {}", editor.path().display()))
+        .args(["edit", "--interactive", "--since", "2026-10-17"])
+        .assert()
+        .stdout("1 edited, 0 deleted, 0 added\n")
+        .success();
+
+    let log = String::from_utf8(track(&db, &lockfile)?.arg("log").output()?.stdout)?;
+    assert!(log.ends_with("2026-10-17 09:00  2026-10-17 11:00  02:00:00  initech\n"));
+
+    Ok(())
+}
+
+#[test]
+fn stop_can_be_undone_and_redone() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+    track(&db, &lockfile)?
+        .arg("undo")
+        .assert()
+        .stdout("nothing to undo\n")
+        .success();
+    start_tracking(&db, &lockfile)?;
+    stop_tracking(&db, &lockfile)?;
+
+    track(&db, &lockfile)?
+        .arg("undo")
+        .assert()
+        .stdout("undid stop\n")
+        .success();
+    assert!(lockfile.exists());
+    track(&db, &lockfile)?
+        .arg("log")
+        .assert()
+        .stdout("")
+        .success();
+
+    track(&db, &lockfile)?
+        .arg("redo")
+        .assert()
+        .stdout("redid stop\n")
+        .success();
+    assert!(!lockfile.exists());
+    track(&db, &lockfile)?
+        .arg("redo")
+        .assert()
+        .stdout("nothing to redo\n")
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn status_command_shows_running_session() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+    track(&db, &lockfile)?
+        .arg("status")
+        .assert()
+        .stdout("stopped\n")
+        .code(3);
+
+    track(&db, &lockfile)?
+        .args([
+            "start",
+            "--at",
+            "2026-10-17 09:00",
+            "-p",
+            "acme",
+            "-t",
+            "deep",
+        ])
+        .assert()
+        .success();
+    let output = track(&db, &lockfile)?.arg("status").output()?;
+    let status = String::from_utf8(output.stdout)?;
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(status.starts_with("running\nstarted  2026-10-17 09:00:00\nelapsed  "));
+    assert!(status.ends_with("project  acme\ntags     deep\n"));
+
+    Ok(())
+}
+
+#[test]
+fn report_covers_requested_timespan() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+    for (from, to) in [
+        ("2026-10-16 09:00", "2026-10-16 11:00"),
+        ("2026-10-17 09:00", "2026-10-17 10:00"),
+    ] {
+        track(&db, &lockfile)?
+            .args(["add", "--from", from, "--to", to])
+            .assert()
+            .success();
+    }
+
+    track(&db, &lockfile)?
+        .args(["report", "--from", "2026-10-17", "--to", "2026-10-18"])
+        .assert()
+        .stdout("01:00:00\n")
+        .success();
+    track(&db, &lockfile)?
+        .args(["report", "--from", "2026-10-16", "--to", "2026-10-17"])
+        .assert()
+        .stdout("02:00:00\n")
+        .success();
+    track(&db, &lockfile)?
+        .args(["report", "--from", "2026-10-18", "--to", "2026-10-16"])
+        .assert()
+        .failure();
+    track(&db, &lockfile)?
+        .args(["report", "--today", "--week"])
+        .assert()
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn report_includes_running_session() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+    track(&db, &lockfile)?
+        .args(["start", "--ago", "1h", "-p", "acme"])
+        .assert()
+        .success();
+
+    let report = String::from_utf8(track(&db, &lockfile)?.arg("report").output()?.stdout)?;
+    assert!(report.starts_with("01:00:0"));
+    assert!(report.ends_with("  (running)\n"));
+
+    let grouped = String::from_utf8(
+        track(&db, &lockfile)?
+            .args(["report", "--by", "project"])
+            .output()?
+            .stdout,
+    )?;
+    assert!(grouped.starts_with("acme   01:00:0"));
+    assert!(grouped.ends_with("  (running)\n"));
+
+    track(&db, &lockfile)?
+        .args(["report", "--no-running"])
+        .assert()
+        .stdout("00:00:00\n")
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn report_breaks_down_by_day_and_project() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+    for (from, to, project) in [
+        ("2026-10-16 22:00", "2026-10-17 01:00", "acme"),
+        ("2026-10-17 09:00", "2026-10-17 10:30", "initech"),
+    ] {
+        track(&db, &lockfile)?
+            .args(["add", "--from", from, "--to", to, "-p", project])
+            .assert()
+            .success();
+    }
+
+    track(&db, &lockfile)?
+        .args([
+            "report",
+            "--from",
+            "2026-10-16",
+            "--to",
+            "2026-10-18",
+            "--by",
+            "day,project",
+        ])
+        .assert()
+        .stdout(
+            "2026-10-16  02:00:00\n\
+             \x20 acme      02:00:00\n\
+             2026-10-17  02:30:00\n\
+             \x20 acme      01:00:00\n\
+             \x20 initech   01:30:00\n\
+             total       04:30:00\n",
+        )
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn report_and_log_can_be_machine_readable() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+    track(&db, &lockfile)?
+        .args([
+            "add",
+            "--from",
+            "2026-10-17 09:00",
+            "--to",
+            "2026-10-17 10:30",
+            "-p",
+            "acme",
+        ])
+        .assert()
+        .success();
+    let window = ["--from", "2026-10-17", "--to", "2026-10-18"];
+
+    track(&db, &lockfile)?
+        .arg("report")
+        .args(window)
+        .args(["--by", "project", "--output", "csv"])
+        .assert()
+        .stdout(
+            "from,to,project,seconds,duration,running\n\
+             2026-10-17T00:00:00+00:00,2026-10-18T00:00:00+00:00,acme,5400,01:30:00,false\n",
+        )
+        .success();
+
+    let output = track(&db, &lockfile)?
+        .arg("report")
+        .args(window)
+        .args(["--output", "json"])
+        .output()?;
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(report["total"]["seconds"], 5400);
+    assert_eq!(report["from"], "2026-10-17T00:00:00Z");
+    assert_eq!(report["records"][0]["project"], "acme");
+
+    let output = track(&db, &lockfile)?
+        .args(["log", "--output", "tsv"])
+        .output()?;
+    let log = String::from_utf8(output.stdout)?;
+    assert_eq!(log.lines().count(), 2);
+    let row = log.lines().nth(1).ok_or("no record")?;
+    assert!(row.contains("\t5400\t01:30:00\tacme\t"));
+
+    Ok(())
+}
+
+#[test]
+fn report_durations_can_be_formatted() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+    track(&db, &lockfile)?
+        .args([
+            "add",
+            "--from",
+            "2026-10-17 09:00",
+            "--to",
+            "2026-10-17 16:15",
+            "-p",
+            "acme",
+        ])
+        .assert()
+        .success();
+    let window = ["report", "--from", "2026-10-17", "--to", "2026-10-18"];
+
+    for (format, expected) in [
+        ("decimal", "7.25h\n"),
+        ("human", "7 hours 15 minutes\n"),
+        ("iso", "PT7H15M\n"),
+        ("compact", "7h15m\n"),
+    ] {
+        track(&db, &lockfile)?
+            .args(window)
+            .args(["--duration-format", format])
+            .assert()
+            .stdout(expected)
+            .success();
+    }
+
+    track(&db, &lockfile)?
+        .args(window)
+        .args(["--by", "project"])
+        .env("TRACK_DURATION_FORMAT", "decimal")
+        .assert()
+        .stdout("acme   7.25h\ntotal  7.25h\n")
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn report_shows_billable_time() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+    for (from, to, project) in [
+        ("2026-10-17 09:00", "2026-10-17 09:20", "acme"),
+        ("2026-10-17 10:00", "2026-10-17 10:20", "acme"),
+        ("2026-10-17 11:00", "2026-10-17 11:05", "initech"),
+    ] {
+        track(&db, &lockfile)?
+            .args(["add", "--from", from, "--to", to, "-p", project])
+            .assert()
+            .success();
+    }
+    let window = ["report", "--from", "2026-10-17", "--to", "2026-10-18"];
+
+    track(&db, &lockfile)?
+        .args(window)
+        .args(["--by", "project", "--round", "up", "--round-to", "30"])
+        .assert()
+        .stdout(
+            "acme     00:40:00  billed 01:00:00\n\
+             initech  00:05:00  billed 00:30:00\n\
+             total    00:45:00  billed 01:30:00\n",
+        )
+        .success();
+
+    track(&db, &lockfile)?
+        .args(window)
+        .args(["--round", "up", "--round-to", "30", "--round-per", "group"])
+        .assert()
+        .stdout("00:45:00  billed 01:00:00\n")
+        .success();
+
+    track(&db, &lockfile)?
+        .args(window)
+        .args(["--min-billable", "15m"])
+        .assert()
+        .stdout("00:45:00  billed 00:55:00\n")
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn times_are_read_and_shown_in_configured_timezone() -> TestResult {
+    let (temp, db, lockfile) = temp_paths();
+    let config = temp.child("config.toml");
+    config.write_str("timezone = \"Europe/Berlin\"\n")?;
+    // clocks in Berlin go back from 03:00 to 02:00 on 2026-10-25
+    track(&db, &lockfile)?
+        .args([
+            "add",
+            "--from",
+            "2026-10-24 23:00",
+            "--to",
+            "2026-10-26 01:00",
+        ])
+        .assert()
+        .success();
+
+    let output = track(&db, &lockfile)?.arg("log").output()?;
+    assert!(String::from_utf8(output.stdout)?
+        .contains("  2026-10-24 23:00  2026-10-26 01:00  27:00:00  "));
+
+    track(&db, &lockfile)?
+        .args(["report", "--from", "2026-10-25", "--to", "2026-10-26"])
+        .assert()
+        .stdout("25:00:00\n")
+        .success();
+
+    let output = track(&db, &lockfile)?
+        .env("TRACK_TZ", "UTC")
+        .arg("log")
+        .output()?;
+    assert!(String::from_utf8(output.stdout)?.contains("  2026-10-24 21:00  2026-10-26 00:00  "));
+
+    let output = track(&db, &lockfile)?
+        .args(["--tz", "Asia/Tokyo", "log"])
+        .output()?;
+    assert!(String::from_utf8(output.stdout)?.contains("  2026-10-25 06:00  2026-10-26 09:00  "));
+
+    track(&db, &lockfile)?
+        .args(["--tz", "Mars/Olympus_Mons", "log"])
+        .assert()
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn unknown_config_settings_are_ignored_with_a_warning() -> TestResult {
+    let (temp, db, lockfile) = temp_paths();
+    let config = temp.child("config.toml");
+    config.write_str("time_zone = \"Europe/Berlin\"\n")?;
+
+    track(&db, &lockfile)?
+        .arg("log")
+        .assert()
+        .stdout("")
+        .stderr(format!(
+            "warning: ignoring unknown setting 'time_zone' in '{}'\n",
+            config.path().display()
+        ))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn falling_back_to_backup_is_reported() -> TestResult {
+    let (_temp, db, lockfile) = temp_paths();
+    for _ in 0..2 {
+        start_tracking(&db, &lockfile)?;
+        stop_tracking(&db, &lockfile)?;
+    }
+    db.write_str("{\"records\": [")?;
+
+    let output = track(&db, &lockfile)?.arg("log").output()?;
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?.lines().count(), 1);
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.starts_with(&format!(
+        "warning: the database '{}' is corrupted, using its backup",
+        db.path().display()
+    )));
+    assert!(stderr.contains("suggestion: "));
+    Ok(())
+}